@@ -0,0 +1,265 @@
+use std::{collections::HashMap, str::FromStr};
+
+use strum_macros::Display;
+
+use crate::{
+    ACCOUNTS_HEADER, Transaction, error::ParserError, parse_u64_value, split_csv_line};
+
+/// Типы счетов
+#[derive(Display, Debug, PartialEq, Eq, Hash, Clone)]
+pub enum AccountType {
+    /// - CHECKING – текущий счет
+    CHECKING,
+    /// - SAVINGS – сберегательный счет
+    SAVINGS,
+    /// - BUSINESS – счет юридического лица
+    BUSINESS,
+    /// - SYSTEM – системный счет банка
+    SYSTEM,
+}
+
+impl FromStr for AccountType {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CHECKING" => Ok(Self::CHECKING),
+            "SAVINGS" => Ok(Self::SAVINGS),
+            "BUSINESS" => Ok(Self::BUSINESS),
+            "SYSTEM" => Ok(Self::SYSTEM),
+            _ => Err(ParserError::InvalidAccountValue(s.to_string())),
+        }
+    }
+}
+
+/// Состояние счета
+#[derive(Display, Debug, PartialEq, Eq, Hash, Clone)]
+pub enum AccountStatus {
+    /// - ACTIVE – счет открыт
+    ACTIVE,
+    /// - FROZEN – операции по счету заблокированы
+    FROZEN,
+    /// - CLOSED – счет закрыт
+    CLOSED,
+}
+
+impl FromStr for AccountStatus {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ACTIVE" => Ok(Self::ACTIVE),
+            "FROZEN" => Ok(Self::FROZEN),
+            "CLOSED" => Ok(Self::CLOSED),
+            _ => Err(ParserError::InvalidAccountValue(s.to_string())),
+        }
+    }
+}
+
+/// Запись реестра счетов
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Account {
+    /// - user_id – идентификатор пользователя (счета)
+    pub user_id: u64,
+    /// - name – наименование владельца счета
+    pub name: String,
+    /// - account_type – тип счета
+    pub account_type: AccountType,
+    /// - opened_at – Unix epoch timestamp открытия счета в миллисекундах
+    pub opened_at: u64,
+    /// - closed_at – Unix epoch timestamp закрытия счета в миллисекундах (None - счет не закрывался)
+    pub closed_at: Option<u64>,
+    /// - status – состояние счета
+    pub status: AccountStatus,
+}
+
+/// Парсер реестра счетов
+/// (формат CSV: USER_ID,NAME,ACCOUNT_TYPE,OPENED_AT,CLOSED_AT,STATUS, пустой CLOSED_AT - счет не закрывался)
+#[derive(Default)]
+pub struct AccountsParser {
+
+}
+
+impl AccountsParser {
+    /// Функция чтения реестра счетов из источника
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_read<R: std::io::Read>(&self, source: &mut R) -> Result<Vec<Account>, ParserError> {
+        let mut result: Vec<Account> = Vec::new();
+
+        let mut str_records = String::new();
+        source.read_to_string(&mut str_records)?;
+
+        let str_arr: Vec<&str> = str_records
+            .split("\n")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .filter(|s| !s.starts_with("USER_ID"))
+            .collect();
+
+        for (numb, stx) in str_arr.iter().enumerate() {
+            let tmp_vec: Vec<&str> = split_csv_line(stx);
+
+            if tmp_vec.len() != 6 {
+                return Err(ParserError::InvalidCSVStructure(numb));
+            }
+
+            result.push(Account {
+                user_id: parse_u64_value(tmp_vec[0], numb)?,
+                name: serde_json::from_str::<String>(tmp_vec[1])?,
+                account_type: tmp_vec[2].parse::<AccountType>()?,
+                opened_at: parse_u64_value(tmp_vec[3], numb)?,
+                closed_at: match tmp_vec[4] {
+                    "" => None,
+                    val => Some(parse_u64_value(val, numb)?),
+                },
+                status: tmp_vec[5].parse::<AccountStatus>()?,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Функция записи реестра счетов в источник
+    /// * target - получатель данных реализуйщий трейт Write (файл, буфер),
+    /// * data - записи реестра счетов
+    pub fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[Account]) -> Result<(), ParserError> {
+        let mut result_str = String::from(ACCOUNTS_HEADER);
+
+        for account in data {
+            result_str += format!("{},\"{}\",{},{},{},{}\n",
+                account.user_id,
+                account.name,
+                account.account_type,
+                account.opened_at,
+                account.closed_at.map(|val| val.to_string()).unwrap_or_default(),
+                account.status).as_str();
+        }
+        target.write_all(result_str.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Виды нарушений ссылочной целостности
+#[derive(Display, Debug, PartialEq, Eq, Clone)]
+pub enum IntegrityIssueKind {
+    /// - UNKNOWN_ACCOUNT – счет отсутствует в реестре
+    #[strum(serialize = "UNKNOWN_ACCOUNT")]
+    UnknownAccount,
+    /// - CLOSED_ACCOUNT – транзакция по закрытому счету
+    #[strum(serialize = "CLOSED_ACCOUNT")]
+    ClosedAccount,
+    /// - FROZEN_ACCOUNT – транзакция по заблокированному счету
+    #[strum(serialize = "FROZEN_ACCOUNT")]
+    FrozenAccount,
+    /// - OUTSIDE_OPEN_PERIOD – транзакция вне периода действия счета
+    #[strum(serialize = "OUTSIDE_OPEN_PERIOD")]
+    OutsideOpenPeriod,
+}
+
+/// Нарушение ссылочной целостности транзакции
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IntegrityIssue {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - user_id – идентификатор счета, на который ссылается транзакция
+    pub user_id: u64,
+    /// - kind – вид нарушения
+    pub kind: IntegrityIssueKind,
+}
+
+/// Функция проверки ссылочной целостности транзакций
+///
+/// Проверяются счета, затрагиваемые транзакцией (см. [`Transaction::participants`]).
+/// Для каждого счета транзакции фиксируется не более одного нарушения.
+///
+/// # Аргументы
+///
+/// * accounts: &[Account] - реестр счетов
+/// * data: &[Transaction] - транзакции
+///
+/// # Возвращаемое значение
+/// Список нарушений в порядке следования транзакций
+pub fn check_integrity(accounts: &[Account], data: &[Transaction]) -> Vec<IntegrityIssue> {
+    let registry: HashMap<u64, &Account> = accounts.iter().map(|account| (account.user_id, account)).collect();
+    let mut result: Vec<IntegrityIssue> = Vec::new();
+
+    for tx in data {
+        for user_id in tx.participants() {
+            let kind = match registry.get(&user_id) {
+                None => Some(IntegrityIssueKind::UnknownAccount),
+                Some(account) => account_issue(account, tx.timestamp),
+            };
+            if let Some(kind) = kind {
+                result.push(IntegrityIssue { tx_id: tx.tx_id, user_id, kind });
+            }
+        }
+    }
+    result
+}
+
+fn account_issue(account: &Account, timestamp: u64) -> Option<IntegrityIssueKind> {
+    let after_close = account.closed_at.is_some_and(|closed_at| timestamp >= closed_at);
+
+    if timestamp < account.opened_at {
+        return Some(IntegrityIssueKind::OutsideOpenPeriod);
+    }
+    match account.status {
+        AccountStatus::CLOSED if after_close || account.closed_at.is_none() => Some(IntegrityIssueKind::ClosedAccount),
+        AccountStatus::FROZEN => Some(IntegrityIssueKind::FrozenAccount),
+        _ if after_close => Some(IntegrityIssueKind::OutsideOpenPeriod),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    fn account(user_id: u64, opened_at: u64, closed_at: Option<u64>, status: AccountStatus) -> Account {
+        Account { user_id, name: format!("User {}", user_id), account_type: AccountType::CHECKING, opened_at,
+            closed_at, status }
+    }
+
+    #[test]
+    fn test_accounts_round_trip() -> Result<(), ParserError> {
+        let data = vec![
+            account(10, 1000, None, AccountStatus::ACTIVE),
+            account(20, 1000, Some(5000), AccountStatus::CLOSED),
+        ];
+        let parser = AccountsParser::default();
+        let mut buf: Vec<u8> = Vec::new();
+        parser.write_to(&mut buf, &data)?;
+        assert_eq!(String::from_utf8_lossy(&buf), ACCOUNTS_HEADER.to_owned() +
+            "10,\"User 10\",CHECKING,1000,,ACTIVE\n20,\"User 20\",CHECKING,1000,5000,CLOSED\n");
+        assert_eq!(parser.from_read(&mut buf.as_slice())?, data);
+
+        let invalid = "10,\"User 10\",LOAN,1000,,ACTIVE\n";
+        assert!(matches!(parser.from_read(&mut invalid.as_bytes()), Err(ParserError::InvalidAccountValue(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_integrity() -> Result<(), Box<dyn std::error::Error>> {
+        let accounts = vec![
+            account(10, 1000, None, AccountStatus::ACTIVE),
+            account(20, 1000, Some(5000), AccountStatus::CLOSED),
+            account(30, 1000, None, AccountStatus::FROZEN),
+        ];
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).timestamp(2000).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 100).timestamp(3000).build()?,
+            test_tx(3, TransactionType::TRANSFER, 10, 20, 100).timestamp(6000).build()?,
+            test_tx(4, TransactionType::WITHDRAWAL, 30, 0, 100).timestamp(2000).build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 40, 100).timestamp(2000).build()?,
+            test_tx(6, TransactionType::DEPOSIT, 0, 10, 100).timestamp(500).build()?,
+        ];
+        assert_eq!(check_integrity(&accounts, &data), vec![
+            IntegrityIssue { tx_id: 3, user_id: 20, kind: IntegrityIssueKind::ClosedAccount },
+            IntegrityIssue { tx_id: 4, user_id: 30, kind: IntegrityIssueKind::FrozenAccount },
+            IntegrityIssue { tx_id: 5, user_id: 40, kind: IntegrityIssueKind::UnknownAccount },
+            IntegrityIssue { tx_id: 6, user_id: 10, kind: IntegrityIssueKind::OutsideOpenPeriod },
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    Transaction,
+    error::ParserError,
+    stats::{Bucket, GroupBy},
+};
+
+/// Группировка сверки по умолчанию: пользователь, тип, статус, сутки
+pub const DEFAULT_AGGREGATE_BY: [GroupBy; 4] = [GroupBy::User, GroupBy::Type, GroupBy::Status, GroupBy::Day];
+
+/// Итоги группы транзакций
+#[derive(Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct BucketTotals {
+    /// - count – число транзакций
+    pub count: u64,
+    /// - total – сумма
+    pub total: u128,
+}
+
+/// Группа, итоги которой в наборах различаются
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct BucketDiff {
+    /// - key – значения ключей группы в порядке группировки
+    pub key: Vec<String>,
+    /// - left – итоги первого набора
+    pub left: BucketTotals,
+    /// - right – итоги второго набора
+    pub right: BucketTotals,
+    /// - count_delta – разница числа транзакций (второй набор - первый)
+    pub count_delta: i128,
+    /// - total_delta – разница сумм (второй набор - первый)
+    pub total_delta: i128,
+}
+
+/// Результат сверки наборов по итогам групп
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct AggregateReport {
+    /// - group_by – способы группировки
+    pub group_by: Vec<String>,
+    /// - buckets – число сравненных групп
+    pub buckets: usize,
+    /// - left – итоги первого набора
+    pub left: BucketTotals,
+    /// - right – итоги второго набора
+    pub right: BucketTotals,
+    /// - differences – группы с различающимися итогами в порядке ключей
+    pub differences: Vec<BucketDiff>,
+}
+
+impl AggregateReport {
+    /// Возвращает true, если итоги всех групп совпадают
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+fn totals_by(data: &[Transaction], group_by: &[GroupBy]) -> (BucketTotals, BTreeMap<Vec<Bucket>, BucketTotals>) {
+    let mut overall = BucketTotals::default();
+    let mut buckets: BTreeMap<Vec<Bucket>, BucketTotals> = BTreeMap::new();
+
+    for tx in data {
+        overall.count += 1;
+        overall.total += tx.amount as u128;
+
+        let mut keys: Vec<Vec<Bucket>> = vec![Vec::new()];
+        for dimension in group_by {
+            let values = Bucket::directed(tx, *dimension);
+            keys = keys
+                .into_iter()
+                .flat_map(|key| values.iter().map(move |val| [key.as_slice(), std::slice::from_ref(val)].concat()))
+                .collect();
+        }
+        for key in keys {
+            let totals = buckets.entry(key).or_default();
+            totals.count += 1;
+            totals.total += tx.amount as u128;
+        }
+    }
+    (overall, buckets)
+}
+
+/// Функция сверки наборов транзакций по итогам групп
+///
+/// Транзакции обоих наборов группируются по сочетанию значений group_by, для групп считаются число
+/// транзакций и сумма. Группа пользователя учитывает направление: перевод относится к группе списания
+/// отправителя (ключ "10 DEBIT") и к группе зачисления получателя (ключ "20 CREDIT"), поэтому наборы
+/// с переставленными участниками перевода различаются. В отчет попадают только группы, итоги которых различаются.
+///
+/// # Аргументы
+///
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+/// * group_by: &[GroupBy] - способы группировки, например [`DEFAULT_AGGREGATE_BY`]
+///
+/// # Возвращаемое значение
+/// AggregateReport
+///
+/// # Пример
+/// ```ignore
+/// let report = reconcile_aggregates(&bank_vec, &processing_vec, &DEFAULT_AGGREGATE_BY);
+/// for diff in &report.differences {
+///     println!("{}: {}", diff.key.join(" "), diff.total_delta);
+/// }
+/// ```
+pub fn reconcile_aggregates(left_side: &[Transaction], right_side: &[Transaction],
+    group_by: &[GroupBy]) -> AggregateReport {
+
+    let (left, left_buckets) = totals_by(left_side, group_by);
+    let (right, right_buckets) = totals_by(right_side, group_by);
+
+    let mut merged: BTreeMap<&Vec<Bucket>, (BucketTotals, BucketTotals)> = BTreeMap::new();
+    for (key, totals) in &left_buckets {
+        merged.entry(key).or_default().0 = *totals;
+    }
+    for (key, totals) in &right_buckets {
+        merged.entry(key).or_default().1 = *totals;
+    }
+
+    let buckets = merged.len();
+    let differences = merged
+        .into_iter()
+        .filter(|(_, (left, right))| left != right)
+        .map(|(key, (left, right))| BucketDiff {
+            key: key.iter().map(|bucket| bucket.label()).collect(),
+            left,
+            right,
+            count_delta: right.count as i128 - left.count as i128,
+            total_delta: right.total as i128 - left.total as i128,
+        })
+        .collect();
+
+    AggregateReport {
+        group_by: group_by.iter().map(|dimension| dimension.to_string()).collect(),
+        buckets,
+        left,
+        right,
+        differences,
+    }
+}
+
+#[derive(Serialize)]
+struct AggregateDocument<'a> {
+    equal: bool,
+    #[serde(flatten)]
+    report: &'a AggregateReport,
+}
+
+/// Функция записи результата сверки по итогам групп в формате JSON
+///
+/// Документ содержит признак совпадения итогов (equal) и поля [`AggregateReport`].
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер, stdout)
+/// * report - результат сверки
+pub fn write_aggregate_json<W: std::io::Write>(target: &mut W, report: &AggregateReport) -> Result<(), ParserError> {
+    serde_json::to_writer_pretty(&mut *target, &AggregateDocument { equal: report.is_empty(), report })?;
+    target.write_all(b"\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    #[test]
+    fn test_reconcile_aggregates() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).timestamp(1_633_036_860_000).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 200).timestamp(1_633_036_860_000).build()?,
+            test_tx(3, TransactionType::TRANSFER, 10, 20, 50).timestamp(1_633_036_860_000).build()?,
+        ];
+        let right = vec![
+            test_tx(11, TransactionType::DEPOSIT, 0, 10, 300).timestamp(1_633_036_860_000).build()?,
+            test_tx(13, TransactionType::TRANSFER, 10, 20, 70).timestamp(1_633_036_860_000).build()?,
+        ];
+
+        let report = reconcile_aggregates(&left, &right, &DEFAULT_AGGREGATE_BY);
+        assert_eq!(report.buckets, 3);
+        assert_eq!(report.left, BucketTotals { count: 3, total: 350 });
+        assert_eq!(report.right, BucketTotals { count: 2, total: 370 });
+
+        let differences: Vec<(String, i128, i128)> = report.differences
+            .iter()
+            .map(|diff| (diff.key.join(" "), diff.count_delta, diff.total_delta))
+            .collect();
+        assert_eq!(differences, vec![
+            ("10 DEBIT TRANSFER SUCCESS 2021-09-30".to_owned(), 0, 20),
+            ("10 CREDIT DEPOSIT SUCCESS 2021-09-30".to_owned(), -1, 0),
+            ("20 CREDIT TRANSFER SUCCESS 2021-09-30".to_owned(), 0, 20),
+        ]);
+
+        let by_type = reconcile_aggregates(&left, &right, &[GroupBy::Type]);
+        assert_eq!(by_type.differences.len(), 2);
+        assert!(reconcile_aggregates(&left, &left, &DEFAULT_AGGREGATE_BY).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_swapped_parties() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?];
+        let right = vec![test_tx(1, TransactionType::TRANSFER, 20, 10, 100).build()?];
+
+        let report = reconcile_aggregates(&left, &right, &[GroupBy::User]);
+        let differences: Vec<(String, i128, i128)> = report.differences
+            .iter()
+            .map(|diff| (diff.key.join(" "), diff.count_delta, diff.total_delta))
+            .collect();
+        assert_eq!(differences, vec![
+            ("10 DEBIT".to_owned(), -1, -100),
+            ("10 CREDIT".to_owned(), 1, 100),
+            ("20 DEBIT".to_owned(), 1, 100),
+            ("20 CREDIT".to_owned(), -1, -100),
+        ]);
+        assert_eq!(differences.iter().map(|(_, _, total_delta)| total_delta).sum::<i128>(), 0);
+        assert!(reconcile_aggregates(&left, &right, &[GroupBy::Type]).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+use std::str::FromStr;
+
+use crate::error::AmountError;
+
+/// Правила округления дробной части суммы при чтении
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// - Exact – округление запрещено, лишние значащие разряды приводят к ошибке
+    #[default]
+    Exact,
+    /// - HalfUp – к ближайшему, половина округляется от нуля
+    HalfUp,
+    /// - HalfEven – к ближайшему, половина округляется к четному (банковское округление)
+    HalfEven,
+    /// - Down – отбрасывание лишних разрядов
+    Down,
+    /// - Up – округление вверх при наличии ненулевых лишних разрядов
+    Up,
+}
+
+impl FromStr for Rounding {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "exact" => Ok(Self::Exact),
+            "half-up" => Ok(Self::HalfUp),
+            "half-even" => Ok(Self::HalfEven),
+            "down" => Ok(Self::Down),
+            "up" => Ok(Self::Up),
+            _ => Err(AmountError::Malformed(s.to_string())),
+        }
+    }
+}
+
+/// Локали с предопределенными разделителями целой и дробной части и групп разрядов
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    /// - En – `1,250.75`
+    En,
+    /// - Ru – `1 250,75`
+    Ru,
+    /// - De – `1.250,75`
+    De,
+    /// - Ch – `1'250.75`
+    Ch,
+}
+
+impl Locale {
+    /// Возвращает пару (разделитель дробной части, разделитель групп разрядов)
+    pub fn separators(&self) -> (char, char) {
+        match self {
+            Self::En => ('.', ','),
+            Self::Ru => (',', ' '),
+            Self::De => (',', '.'),
+            Self::Ch => ('.', '\''),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" => Ok(Self::En),
+            "ru" => Ok(Self::Ru),
+            "de" => Ok(Self::De),
+            "ch" => Ok(Self::Ch),
+            _ => Err(AmountError::UnknownLocale(s.to_string())),
+        }
+    }
+}
+
+/// Возвращает число разрядов дробной части (минорных единиц) для валюты по ISO 4217.
+/// Для валют, отсутствующих в таблице исключений, используется 2.
+pub fn currency_scale(currency: &str) -> u32 {
+    match currency.to_ascii_uppercase().as_str() {
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" | "PYG" | "UGX" | "XAF" | "XOF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Формат записи суммы в текстовых форматах (TXT, CSV).
+///
+/// Внутри транзакции сумма всегда хранится в целых минорных единицах,
+/// формат определяет только ее текстовое представление.
+/// По умолчанию сумма записывается как целое число минорных единиц (scale = 0).
+///
+/// # Пример
+/// ```ignore
+/// let fmt = AmountFormat::for_currency("RUB").with_locale(Locale::Ru);
+/// assert_eq!(fmt.parse("1 250,75")?, 125075);
+/// assert_eq!(fmt.format(125075), "1 250,75");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmountFormat {
+    /// - scale – число разрядов дробной части
+    pub scale: u32,
+    /// - decimal_separator – разделитель целой и дробной части
+    pub decimal_separator: char,
+    /// - group_separator – разделитель групп разрядов (None - без группировки)
+    pub group_separator: Option<char>,
+    /// - rounding – правило округления при чтении
+    pub rounding: Rounding,
+}
+
+impl Default for AmountFormat {
+    fn default() -> Self {
+        Self::minor_units()
+    }
+}
+
+impl AmountFormat {
+    /// Формат целых минорных единиц (`125075`)
+    pub fn minor_units() -> Self {
+        Self::decimal(0)
+    }
+
+    /// Десятичный формат с заданным числом разрядов дробной части (`1250.75`)
+    pub fn decimal(scale: u32) -> Self {
+        Self {
+            scale,
+            decimal_separator: '.',
+            group_separator: None,
+            rounding: Rounding::Exact,
+        }
+    }
+
+    /// Десятичный формат с числом разрядов дробной части, определенным для валюты
+    pub fn for_currency(currency: &str) -> Self {
+        Self::decimal(currency_scale(currency))
+    }
+
+    /// Устанавливает разделители в соответствии с локалью
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        let (decimal, group) = locale.separators();
+        self.decimal_separator = decimal;
+        self.group_separator = Some(group);
+        self
+    }
+
+    /// Устанавливает правило округления
+    pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Функция чтения суммы из текстового представления
+    ///
+    /// Разделители групп допускаются только в целой части: первая группа из 1-3 цифр,
+    /// остальные ровно из 3 цифр. Запись без разделителей групп также допускается.
+    ///
+    /// # Аргументы
+    ///
+    /// * val: &str - сумма в десятичной записи
+    ///
+    /// # Возвращаемое значение
+    /// Сумма в минорных единицах
+    ///
+    /// # Ошибки
+    /// AmountError - некорректная запись, потеря точности при Rounding::Exact, переполнение.
+    pub fn parse(&self, val: &str) -> Result<u64, AmountError> {
+        let malformed = || AmountError::Malformed(val.to_string());
+        let trimmed = val.trim();
+
+        let (int_part, frac_part) = match trimmed.split_once(self.decimal_separator) {
+            Some((int_part, frac_part)) => (int_part, frac_part),
+            None => (trimmed, ""),
+        };
+
+        // Группы разрядов: первая из 1-3 цифр, остальные ровно из 3 цифр
+        let groups: Vec<&str> = int_part.split(|ch| self.is_group_separator(ch)).collect();
+        let is_digits = |part: &str| part.chars().all(|ch| ch.is_ascii_digit());
+        if !groups.iter().all(|group| is_digits(group))
+            || (groups.len() > 1
+                && (!(1..=3).contains(&groups[0].len()) || groups[1..].iter().any(|group| group.len() != 3)))
+        {
+            return Err(malformed());
+        }
+        let int_digits = groups.concat();
+
+        if !is_digits(frac_part) || int_digits.len() + frac_part.len() == 0 {
+            return Err(malformed());
+        }
+
+        let scale = self.scale as usize;
+        let (kept, dropped) = frac_part.split_at(frac_part.len().min(scale));
+
+        let mut result: u64 = 0;
+        for ch in int_digits.chars().chain(kept.chars()).chain("0".repeat(scale - kept.len()).chars()) {
+            result = result
+                .checked_mul(10)
+                .and_then(|r| r.checked_add(ch.to_digit(10).unwrap_or(0) as u64))
+                .ok_or_else(|| AmountError::Overflow(val.to_string()))?;
+        }
+
+        if self.round_up(result, dropped).ok_or_else(|| AmountError::Precision(val.to_string()))? {
+            result = result.checked_add(1).ok_or_else(|| AmountError::Overflow(val.to_string()))?;
+        }
+        Ok(result)
+    }
+
+    /// Функция записи суммы в текстовое представление
+    ///
+    /// # Аргументы
+    ///
+    /// * amount: u64 - сумма в минорных единицах
+    ///
+    /// # Возвращаемое значение
+    /// Сумма в десятичной записи
+    pub fn format(&self, amount: u64) -> String {
+        // При scale > 19 делитель не помещается в u64, а целая часть любой суммы равна нулю
+        let (int_value, frac_value) = match 10u64.checked_pow(self.scale) {
+            Some(divisor) => (amount / divisor, amount % divisor),
+            None => (0, amount),
+        };
+        let int_str = int_value.to_string();
+
+        let mut result = String::with_capacity(int_str.len() * 2);
+        for (pos, ch) in int_str.chars().enumerate() {
+            if let Some(sep) = self.group_separator
+                && pos > 0
+                && (int_str.len() - pos).is_multiple_of(3)
+            {
+                result.push(sep);
+            }
+            result.push(ch);
+        }
+
+        if self.scale > 0 {
+            result.push(self.decimal_separator);
+            result += format!("{:0width$}", frac_value, width = self.scale as usize).as_str();
+        }
+        result
+    }
+
+    fn is_group_separator(&self, ch: char) -> bool {
+        match self.group_separator {
+            Some(' ') => ch == ' ' || ch == '\u{a0}' || ch == '\u{202f}',
+            Some(sep) => ch == sep,
+            None => false,
+        }
+    }
+
+    /// Определяет необходимость увеличения младшего разряда по отброшенным разрядам.
+    /// Возвращает None, если округление запрещено, а отброшенные разряды ненулевые.
+    fn round_up(&self, kept: u64, dropped: &str) -> Option<bool> {
+        let nonzero = dropped.chars().any(|ch| ch != '0');
+        if !nonzero {
+            return Some(false);
+        }
+
+        let first = dropped.chars().next().and_then(|ch| ch.to_digit(10)).unwrap_or(0);
+        let rest_nonzero = dropped.chars().skip(1).any(|ch| ch != '0');
+
+        match self.rounding {
+            Rounding::Exact => None,
+            Rounding::Down => Some(false),
+            Rounding::Up => Some(true),
+            Rounding::HalfUp => Some(first >= 5),
+            Rounding::HalfEven => Some(first > 5 || (first == 5 && (rest_nonzero || !kept.is_multiple_of(2)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Transaction, TransactionStatus, TransactionType, TransactionsParser, csv_format::CsvParser,
+        txt_format::TxtParser};
+
+    #[test]
+    fn test_parse_and_format_locale() {
+        let fmt = AmountFormat::for_currency("RUB").with_locale(Locale::Ru);
+        assert_eq!(fmt.parse("1 250,75"), Ok(125075));
+        assert_eq!(fmt.parse("1\u{a0}250,7"), Ok(125070));
+        assert_eq!(fmt.parse("12"), Ok(1200));
+        assert_eq!(fmt.format(125075), "1 250,75");
+        assert_eq!(fmt.format(5), "0,05");
+        assert_eq!(AmountFormat::for_currency("JPY").format(1250), "1250");
+        assert_eq!(AmountFormat::for_currency("KWD").parse("1.5"), Ok(1500));
+        assert_eq!(AmountFormat::minor_units().parse("100"), Ok(100));
+    }
+
+    #[test]
+    fn test_parse_rounding() {
+        let fmt = AmountFormat::decimal(2);
+        assert_eq!(fmt.parse("1.005"), Err(AmountError::Precision("1.005".to_owned())));
+        assert_eq!(fmt.parse("1.0050"), Err(AmountError::Precision("1.0050".to_owned())));
+        assert_eq!(fmt.parse("1.000"), Ok(100));
+        assert_eq!(fmt.clone().with_rounding(Rounding::HalfUp).parse("1.005"), Ok(101));
+        assert_eq!(fmt.clone().with_rounding(Rounding::HalfEven).parse("1.005"), Ok(100));
+        assert_eq!(fmt.clone().with_rounding(Rounding::HalfEven).parse("1.015"), Ok(102));
+        assert_eq!(fmt.clone().with_rounding(Rounding::HalfEven).parse("1.0051"), Ok(101));
+        assert_eq!(fmt.clone().with_rounding(Rounding::Down).parse("1.009"), Ok(100));
+        assert_eq!(fmt.clone().with_rounding(Rounding::Up).parse("1.001"), Ok(101));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let fmt = AmountFormat::decimal(2);
+        assert!(matches!(fmt.parse("-1.00"), Err(AmountError::Malformed(_))));
+        assert!(matches!(fmt.parse("1.2.3"), Err(AmountError::Malformed(_))));
+        assert!(matches!(fmt.parse(""), Err(AmountError::Malformed(_))));
+        assert!(matches!(fmt.parse("1 000.00"), Err(AmountError::Malformed(_))));
+        assert!(matches!(fmt.parse("184467440737095516.16"), Err(AmountError::Overflow(_))));
+
+        let en = AmountFormat::decimal(2).with_locale(Locale::En);
+        assert_eq!(en.parse("1,250,000.75"), Ok(125000075));
+        assert_eq!(en.parse("1250000.75"), Ok(125000075));
+        for bad in ["1,25", "1,,2", ",125", "125,", "1250,000", "1,2500", "1,250.7,5", "1.25,"] {
+            assert!(matches!(en.parse(bad), Err(AmountError::Malformed(_))), "{}", bad);
+        }
+
+        let wide = AmountFormat::decimal(20);
+        assert_eq!(wide.format(u64::MAX), "0.18446744073709551615");
+        assert_eq!(wide.parse("0.00000000000000000042"), Ok(42));
+        assert!(matches!(wide.parse("1"), Err(AmountError::Overflow(_))));
+    }
+
+    #[test]
+    fn test_decimal_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let tx = Transaction::builder()
+            .tx_id(1)
+            .tx_type(TransactionType::DEPOSIT)
+            .from_user_id(0)
+            .to_user_id(42)
+            .amount(125075)
+            .timestamp(1633036860000)
+            .status(TransactionStatus::SUCCESS)
+            .description("Decimal, amount")
+            .build()?;
+        let fmt = AmountFormat::for_currency("EUR").with_locale(Locale::Ru);
+
+        let csv_parser = CsvParser::default().amount_format(fmt.clone());
+        let mut buf: Vec<u8> = Vec::new();
+        csv_parser.write_to(&mut buf, std::slice::from_ref(&tx))?;
+        assert!(String::from_utf8(buf.clone())?.contains(",\"1 250,75\","));
+        assert_eq!(csv_parser.from_read(&mut buf.as_slice())?, vec![tx.clone()]);
+
+        let txt_parser = TxtParser::default().amount_format(fmt);
+        let mut buf: Vec<u8> = Vec::new();
+        txt_parser.write_to(&mut buf, std::slice::from_ref(&tx))?;
+        assert!(String::from_utf8(buf.clone())?.contains("AMOUNT: 1 250,75\n"));
+        assert_eq!(txt_parser.from_read(&mut buf.as_slice())?, vec![tx]);
+        Ok(())
+    }
+}
@@ -1,11 +1,86 @@
 use crate::{
-    BIN_BODY_LEN, MAGIC, ParserError, Transaction, TransactionStatus, TransactionType, TransactionsFormatType, TransactionsParser};
+    BIN_BODY_LEN, MAGIC, ParserError, Transaction, TransactionBuilder, TransactionStatus, TransactionType, 
+    TransactionsFormatType, TransactionsParser};
 use std::io::{self, BufReader, Read};
 
 /// Парсер бинарного формата BIN
 #[derive(Default)]
 pub struct BinParser {
-    
+    allow_empty: bool,
+}
+
+impl BinParser {
+    /// Конструктор парсера в режиме обратной совместимости:
+    /// значения `EMPTY` типа и статуса транзакции допускаются при чтении,
+    /// неизвестные коды типа и статуса читаются как `EMPTY`
+    pub fn with_empty_compat() -> Self {
+        Self { allow_empty: true }
+    }
+
+    /// Функция чтения очередной транзакции из источника
+    /// * reader - источник данных, позиционированный на начало записи
+    /// * numb - номер транзакции в наборе
+    ///
+    /// Возвращает None, если источник исчерпан
+    pub(crate) fn read_record<R: Read>(&self, reader: &mut R, numb: usize) -> Result<Option<Transaction>, ParserError> {
+        let mut builder = TransactionBuilder::new().allow_empty(self.allow_empty);
+        let mut buf4 = [0u8; 4];
+
+        match reader.read_exact(&mut buf4) {
+            Ok(_) => (),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => {
+                return Err(ParserError::Io(e))
+            },
+        }
+
+        reader.read_exact(&mut buf4)?;
+
+        let mut buf8 = [0u8; 8];
+        reader.read_exact(&mut buf8)?;
+        builder = builder.tx_id(u64::from_be_bytes(buf8));
+
+        let mut buf1 = [0u8; 1];
+        reader.read_exact(&mut buf1)?;
+        builder = builder.tx_type(
+            match TransactionType::try_from(u8::from_be_bytes(buf1)) {
+                Ok(tx_type) => tx_type,
+                Err(_) if self.allow_empty => TransactionType::EMPTY,
+                Err(source) => return Err(ParserError::InvalidTransaction { tx_numb: numb, source }),
+            });
+
+        reader.read_exact(&mut buf8)?;
+        builder = builder.from_user_id(u64::from_be_bytes(buf8));
+
+        reader.read_exact(&mut buf8)?;
+        builder = builder.to_user_id(u64::from_be_bytes(buf8));
+
+        reader.read_exact(&mut buf8)?;
+        builder = builder.amount(u64::from_be_bytes(buf8));
+
+        reader.read_exact(&mut buf8)?;
+        builder = builder.timestamp(u64::from_be_bytes(buf8));
+
+        reader.read_exact(&mut buf1)?;
+        builder = builder.status(
+            match TransactionStatus::try_from(u8::from_be_bytes(buf1)) {
+                Ok(status) => status,
+                Err(_) if self.allow_empty => TransactionStatus::EMPTY,
+                Err(source) => return Err(ParserError::InvalidTransaction { tx_numb: numb, source }),
+            });
+
+        let mut buf_desc_len: [u8; 4] = [0u8; 4];
+        reader.read_exact(&mut buf_desc_len)?;
+
+        let desc_len: usize = u32::from_be_bytes(buf_desc_len) as usize;
+        let mut buf_desc = vec![0u8; desc_len];
+        reader.read_exact(&mut buf_desc)?;
+        let tx = builder
+            .description(String::from_utf8_lossy(&buf_desc).into_owned().replace("\"", ""))
+            .build()
+            .map_err(|source| ParserError::InvalidTransaction { tx_numb: numb, source })?;
+        Ok(Some(tx))
+    }
 }
 
 impl TransactionsParser for BinParser {
@@ -19,69 +94,16 @@ impl TransactionsParser for BinParser {
         
         let mut reader = BufReader::new(source);
 
-        loop {
-
-            let mut tx = Transaction::new();
-            let mut buf4 = [0u8; 4];
-
-            match reader.read_exact(&mut buf4) {
-                Ok(_) => (),
-                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
-                Err(e) => {
-                    return Err(ParserError::Io(e))
-                },
+        for numb in 0.. {
+            match self.read_record(&mut reader, numb)? {
+                Some(tx) => result.push(tx),
+                None => break,
             }
-
-            reader.read_exact(&mut buf4)?;
-
-            let mut buf8 = [0u8; 8];
-            reader.read_exact(&mut buf8)?;
-            tx.tx_id = u64::from_be_bytes(buf8);
-
-            let mut buf1 = [0u8; 1];
-            reader.read_exact(&mut buf1)?;
-            tx.tx_type = match u8::from_be_bytes(buf1) 
-                {
-                    0 => TransactionType::DEPOSIT, 
-                    1 => TransactionType::TRANSFER, 
-                    2 => TransactionType::WITHDRAWAL, 
-                    _ => TransactionType::EMPTY
-                };
-
-            reader.read_exact(&mut buf8)?;
-            tx.from_user_id = u64::from_be_bytes(buf8);
-
-            reader.read_exact(&mut buf8)?;
-            tx.to_user_id = u64::from_be_bytes(buf8);
-
-            reader.read_exact(&mut buf8)?;
-            tx.amount = u64::from_be_bytes(buf8);
-
-            reader.read_exact(&mut buf8)?;
-            tx.timestamp = u64::from_be_bytes(buf8);
-
-            reader.read_exact(&mut buf1)?;
-            tx.status = match u8::from_be_bytes(buf1)
-            {
-                0 => TransactionStatus::SUCCESS,
-                1 => TransactionStatus::FAILURE,
-                2 => TransactionStatus::PENDING,
-                _ => TransactionStatus::EMPTY
-            };
-
-            let mut buf_desc_len: [u8; 4] = [0u8; 4];
-            reader.read_exact(&mut buf_desc_len)?;
-
-            let desc_len: usize = u32::from_be_bytes(buf_desc_len) as usize;
-            let mut buf_desc = vec![0u8; desc_len];
-            reader.read_exact(&mut buf_desc)?;
-            tx.description = String::from_utf8_lossy(&buf_desc).into_owned().replace("\"", "");
-            result.push(tx);
         }
         Ok(result)
     }
 
-    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &Vec<Transaction>) -> Result<(), ParserError> {
+    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[Transaction]) -> Result<(), ParserError> {
 
         for tx in data {
             target.write_all(MAGIC.as_bytes())?;
@@ -0,0 +1,69 @@
+/// Число миллисекунд в сутках
+pub const MS_PER_DAY: u64 = 86_400_000;
+
+/// Число миллисекунд в секунде
+pub const MS_PER_SECOND: u64 = 1000;
+
+/// Число миллисекунд в часе
+pub const MS_PER_HOUR: u64 = 3_600_000;
+
+/// Возвращает номер суток от начала эпохи
+pub fn day_of(timestamp: u64) -> u64 {
+    timestamp / MS_PER_DAY
+}
+
+/// Возвращает (год, месяц, день) для номера суток от начала эпохи
+pub fn civil_from_days(day: u64) -> (u64, u64, u64) {
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+/// Возвращает true, если сутки являются последними в месяце
+pub fn is_month_end(day: u64) -> bool {
+    civil_from_days(day + 1).2 == 1
+}
+
+/// Возвращает месяц в виде `YYYY-MM`
+pub fn month_label(day: u64) -> String {
+    let (y, m, _) = civil_from_days(day);
+    format!("{:04}-{:02}", y, m)
+}
+
+/// Возвращает дату в виде `YYYY-MM-DD`
+pub fn date_label(day: u64) -> String {
+    let (y, m, d) = civil_from_days(day);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Возвращает дату и время в виде `YYYY-MM-DD HH:MM:SS`
+pub fn datetime_label(timestamp: u64) -> String {
+    let (y, m, d) = civil_from_days(day_of(timestamp));
+    let seconds = (timestamp % MS_PER_DAY) / MS_PER_SECOND;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, m, d, seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(day_of(1633036860000)), (2021, 9, 30));
+        assert_eq!(civil_from_days(19_417), (2023, 3, 1));
+        assert_eq!(datetime_label(1633036860000), "2021-09-30 21:21:00");
+        assert!(is_month_end(19_416));
+        assert!(!is_month_end(19_417));
+        assert_eq!(month_label(10_988), "2000-02");
+        assert!(!is_month_end(10_988 + 27));
+        assert!(is_month_end(10_988 + 28));
+    }
+}
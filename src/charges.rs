@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Transaction, TransactionStatus, TransactionType,
+    calendar::{MS_PER_DAY, civil_from_days, day_of, is_month_end, month_label},
+    error::{BuildError, ChargeError, ParserError},
+    ledger::Ledger,
+};
+
+/// Метка описания транзакции начисления процентов
+pub const INTEREST_TAG: &str = "INTEREST";
+/// Метка описания транзакции комиссии за операцию
+pub const FEE_TAG: &str = "FEE";
+/// Метка описания транзакции ежемесячной платы
+pub const MONTHLY_FEE_TAG: &str = "MONTHLY FEE";
+
+/// Правила начисления процентов и комиссий
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(default)]
+pub struct ChargeRules {
+    /// - interest_rate_bps – годовая процентная ставка на положительный остаток, в базисных пунктах (1% = 100)
+    pub interest_rate_bps: u64,
+    /// - day_count – число дней в году для расчета дневной ставки
+    pub day_count: u64,
+    /// - per_tx_fees – комиссия за успешную транзакцию по ее типу
+    pub per_tx_fees: HashMap<TransactionType, u64>,
+    /// - monthly_fee – ежемесячная плата за обслуживание счета
+    pub monthly_fee: u64,
+    /// - system_user_id – идентификатор счета банка, участвующего в начислениях
+    pub system_user_id: u64,
+    /// - first_tx_id – идентификатор первой создаваемой транзакции, по умолчанию - следующий
+    ///   за наибольшим tx_id исходных транзакций
+    pub first_tx_id: Option<u64>,
+}
+
+impl Default for ChargeRules {
+    fn default() -> Self {
+        Self {
+            interest_rate_bps: 0,
+            day_count: 365,
+            per_tx_fees: HashMap::new(),
+            monthly_fee: 0,
+            system_user_id: 0,
+            first_tx_id: None,
+        }
+    }
+}
+
+impl ChargeRules {
+    /// Функция чтения правил в формате JSON, отсутствующие поля принимают значения по умолчанию
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    pub fn from_read<R: std::io::Read>(source: &mut R) -> Result<Self, ParserError> {
+        Ok(serde_json::from_reader(source)?)
+    }
+}
+
+struct ChargesBuilder<'a> {
+    rules: &'a ChargeRules,
+    ledger: Ledger,
+    /// - next_tx_id – идентификатор следующей транзакции, None - идентификаторы исчерпаны
+    next_tx_id: Option<u64>,
+    result: Vec<Transaction>,
+}
+
+impl ChargesBuilder<'_> {
+    fn push(&mut self, tx_type: TransactionType, user_id: u64, amount: u64, timestamp: u64,
+        description: String) -> Result<(), ChargeError> {
+        let (from_user_id, to_user_id) = match tx_type {
+            TransactionType::DEPOSIT => (self.rules.system_user_id, user_id),
+            _ => (user_id, self.rules.system_user_id),
+        };
+        let tx_id = self.next_tx_id.ok_or(BuildError::IdOverflow)?;
+        let tx = Transaction::builder()
+            .tx_id(tx_id)
+            .tx_type(tx_type)
+            .from_user_id(from_user_id)
+            .to_user_id(to_user_id)
+            .amount(amount)
+            .timestamp(timestamp)
+            .status(TransactionStatus::SUCCESS)
+            .description(description)
+            .build()?;
+        self.ledger.apply(&tx)?;
+        self.next_tx_id = tx_id.checked_add(1);
+        self.result.push(tx);
+        Ok(())
+    }
+}
+
+/// Функция расчета процентов и комиссий по транзакциям
+///
+/// Учитываются успешные транзакции в порядке времени, начиная с балансов `opening`:
+/// * за каждую транзакцию, для типа которой задана комиссия, создается WITHDRAWAL с плательщика
+///   (отправитель для TRANSFER и WITHDRAWAL, получатель для DEPOSIT) с описанием `FEE tx <tx_id>`,
+/// * проценты начисляются ежедневно на положительный остаток на конец суток и выплачиваются
+///   DEPOSIT с описанием `INTEREST YYYY-MM` в последний день месяца и в последний день периода,
+/// * ежемесячная плата списывается WITHDRAWAL с описанием `MONTHLY FEE YYYY-MM` в последний день
+///   каждого полного месяца со всех известных счетов; месяц полный, если период начинается
+///   не позднее его первого дня.
+///
+/// Период расчета - с суток первой по сутки последней транзакции. Создаваемые транзакции нумеруются
+/// с `first_tx_id` (по умолчанию - после наибольшего tx_id в data, чтобы не совпадать с исходными)
+/// и учитываются в балансах при дальнейшем расчете. Счет банка не начисляет и не платит.
+/// Исходные транзакции, переполняющие баланс, пропускаются.
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции
+/// * opening: &BTreeMap<u64, i64> - балансы пользователей на начало периода
+/// * rules: &ChargeRules - правила начисления
+///
+/// # Возвращаемое значение
+/// Созданные транзакции в порядке времени
+///
+/// # Ошибки
+/// * ChargeError::Ledger - начисление переполняет баланс пользователя,
+/// * ChargeError::Build - идентификатор очередной транзакции начисления не помещается в u64.
+///
+/// # Пример
+/// ```ignore
+/// let rules = ChargeRules { interest_rate_bps: 500, monthly_fee: 1000, ..Default::default() };
+/// let charges = compute_charges(&tx_vec, &BTreeMap::new(), &rules)?;
+/// get_parser_for_format(TransactionsFormatType::CSV).write_to(&mut out_file, &charges)?;
+/// ```
+pub fn compute_charges(data: &[Transaction], opening: &BTreeMap<u64, i64>,
+    rules: &ChargeRules) -> Result<Vec<Transaction>, ChargeError> {
+    let mut txs: Vec<&Transaction> = data.iter().filter(|tx| tx.status == TransactionStatus::SUCCESS).collect();
+    txs.sort_by_key(|tx| tx.timestamp);
+
+    let (first_day, last_day) = match (txs.first(), txs.last()) {
+        (Some(first), Some(last)) => (day_of(first.timestamp), day_of(last.timestamp)),
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut builder = ChargesBuilder {
+        rules,
+        ledger: Ledger::with_balances(opening.clone()),
+        next_tx_id: match rules.first_tx_id {
+            Some(first_tx_id) => Some(first_tx_id),
+            None => data.iter().map(|tx| tx.tx_id).max().unwrap_or(0).checked_add(1),
+        },
+        result: Vec::new(),
+    };
+    let interest_divisor = 10_000u128 * rules.day_count.max(1) as u128;
+    let mut accrued: BTreeMap<u64, u128> = BTreeMap::new();
+    let mut pos = 0;
+
+    for day in first_day..=last_day {
+        while pos < txs.len() && day_of(txs[pos].timestamp) == day {
+            let tx = txs[pos];
+            pos += 1;
+            if builder.ledger.apply(tx).is_err() {
+                continue;
+            }
+
+            let fee = rules.per_tx_fees.get(&tx.tx_type).copied().unwrap_or(0);
+            if fee > 0 {
+                let payer = match tx.tx_type {
+                    TransactionType::DEPOSIT => tx.to_user_id,
+                    _ => tx.from_user_id,
+                };
+                builder.push(TransactionType::WITHDRAWAL, payer, fee, tx.timestamp,
+                    format!("{} tx {}", FEE_TAG, tx.tx_id))?;
+            }
+        }
+
+        if rules.interest_rate_bps > 0 {
+            for (user_id, balance) in builder.ledger.balances() {
+                if *balance > 0 && *user_id != rules.system_user_id {
+                    *accrued.entry(*user_id).or_default() += *balance as u128 * rules.interest_rate_bps as u128;
+                }
+            }
+        }
+
+        let end_of_day = (day + 1) * MS_PER_DAY - 1;
+        let month_end = is_month_end(day);
+
+        if month_end || day == last_day {
+            for (user_id, total) in std::mem::take(&mut accrued) {
+                let interest = u64::try_from(total / interest_divisor).unwrap_or(u64::MAX);
+                if interest > 0 {
+                    builder.push(TransactionType::DEPOSIT, user_id, interest, end_of_day,
+                        format!("{} {}", INTEREST_TAG, month_label(day)))?;
+                }
+            }
+        }
+
+        // Первые сутки месяца: номер суток минус (день месяца - 1)
+        let full_month = day + 1 - civil_from_days(day).2 >= first_day;
+        if month_end && full_month && rules.monthly_fee > 0 {
+            let users: Vec<u64> = builder
+                .ledger
+                .balances()
+                .keys()
+                .copied()
+                .filter(|user_id| *user_id != rules.system_user_id)
+                .collect();
+            for user_id in users {
+                builder.push(TransactionType::WITHDRAWAL, user_id, rules.monthly_fee, end_of_day,
+                    format!("{} {}", MONTHLY_FEE_TAG, month_label(day)))?;
+            }
+        }
+    }
+    Ok(builder.result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::LedgerError, test_tx};
+
+    const DAY_2021_10_30: u64 = 18_930 * MS_PER_DAY;
+
+    #[test]
+    fn test_compute_charges() -> Result<(), Box<dyn std::error::Error>> {
+        let json = r#"{"interest_rate_bps": 3650, "per_tx_fees": {"WITHDRAWAL": 5}, "monthly_fee": 10,
+            "first_tx_id": 100}"#;
+        let rules = ChargeRules::from_read(&mut json.as_bytes())?;
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100_000).timestamp(DAY_2021_10_30 + 1000).build()?,
+            test_tx(2, TransactionType::WITHDRAWAL, 10, 0, 200).timestamp(DAY_2021_10_30 + 2 * MS_PER_DAY + 1000)
+                .build()?,
+        ];
+
+        let month_end = DAY_2021_10_30 + 2 * MS_PER_DAY - 1;
+        let period_end = DAY_2021_10_30 + 3 * MS_PER_DAY - 1;
+        assert_eq!(compute_charges(&data, &BTreeMap::new(), &rules)?, vec![
+            test_tx(100, TransactionType::DEPOSIT, 0, 10, 200).timestamp(month_end)
+                .description("INTEREST 2021-10").build()?,
+            test_tx(101, TransactionType::WITHDRAWAL, 10, 0, 5).timestamp(DAY_2021_10_30 + 2 * MS_PER_DAY + 1000)
+                .description("FEE tx 2").build()?,
+            test_tx(102, TransactionType::DEPOSIT, 0, 10, 99).timestamp(period_end)
+                .description("INTEREST 2021-11").build()?,
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_monthly_fee_and_overflow() -> Result<(), Box<dyn std::error::Error>> {
+        let rules = ChargeRules { monthly_fee: 10, ..Default::default() };
+        let (nov_1, dec_1) = (DAY_2021_10_30 + 2 * MS_PER_DAY, DAY_2021_10_30 + 32 * MS_PER_DAY);
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).timestamp(nov_1).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).timestamp(dec_1).build()?,
+        ];
+        let charges = compute_charges(&data, &BTreeMap::new(), &rules)?;
+        let labels: Vec<&str> = charges.iter().map(|tx| tx.description.as_str()).collect();
+        assert_eq!(labels, vec!["MONTHLY FEE 2021-11"]);
+        assert_eq!(charges[0].tx_id, 3);
+
+        let rules = ChargeRules { interest_rate_bps: 3650, ..rules };
+        let opening = BTreeMap::from([(10, i64::MAX - 1000)]);
+        // Исходные транзакции 1 и 2 применяются, переполняет баланс первое начисление процентов (tx 3)
+        assert_eq!(compute_charges(&data, &opening, &rules), Err(LedgerError::Overflow { tx_id: 3, user_id: 10 }.into()));
+
+        let rules = ChargeRules { per_tx_fees: HashMap::from([(TransactionType::DEPOSIT, 1)]), first_tx_id: Some(u64::MAX),
+            ..Default::default() };
+        assert_eq!(compute_charges(&data, &BTreeMap::new(), &rules), Err(BuildError::IdOverflow.into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_rules() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![test_tx(1, TransactionType::DEPOSIT, 0, 10, 100_000).timestamp(DAY_2021_10_30).build()?];
+        assert!(compute_charges(&data, &BTreeMap::new(), &ChargeRules::default())?.is_empty());
+        Ok(())
+    }
+}
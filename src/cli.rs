@@ -0,0 +1,200 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use crate::{TransactionsFormatType, error::ParserError, get_format_value};
+
+/// Подсказка по использованию утилиты bis
+pub const USAGE: &str = "Usage: bis <command> [flags]
+
+Commands:
+  convert     -i <file> -o <file> [--from <fmt>] [--to <fmt>]
+              [--rates <file> --source-currency <code> --reporting-currency <code>
+              [--rounding exact|half-up|half-even|down|up]]
+  compare     -l <file> -r <file> [--left-format <fmt>] [--right-format <fmt>]
+              [--key <field,...>] [--ignore <field,...>] [--timestamp-tolerance <ms>] [--ignore-case]
+              [--normalize-whitespace] [--ordered | --external [--run-size <records>]
+              | --fuzzy [--scoring <rules.json>] | --aggregate [--by <group,...>]] [--report text|json]
+  validate    -i <file> [--from <fmt>]
+  stats       -i <file> [--from <fmt>] [--by all|type|status|user|day|hour] [--report table|json]
+  balances    -i <file> -o <file> [--from <fmt>]
+  duplicates  -i <file> [--from <fmt>] [--replay-window <ms>]
+  merge3      --base <file> -l <file> -r <file> -o <file> [--base-format <fmt>] [--left-format <fmt>]
+              [--right-format <fmt>] [--to <fmt>]
+  diff        --base <file> --new <file> -o <delta-file> [--base-format <fmt>] [--new-format <fmt>]
+  patch       --base <file> --delta <delta-file> -o <file> [--base-format <fmt>] [--to <fmt>]
+  help        print this message
+
+<fmt>: txt|csv|bin, by default taken from the file extension.
+Exit codes: 0 - success, 1 - sets differ, file is invalid, duplicates or merge conflicts found, 2 - error.";
+
+/// Описание флага подкоманды
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flag {
+    /// - name – имя флага без префикса `--`
+    pub name: &'static str,
+    /// - short – короткое имя флага без префикса `-`
+    pub short: Option<&'static str>,
+    /// - takes_value – флаг требует значения
+    pub takes_value: bool,
+}
+
+impl Flag {
+    /// Флаг со значением `--name <value>`
+    pub const fn value(name: &'static str) -> Self {
+        Self { name, short: None, takes_value: true }
+    }
+
+    /// Флаг-переключатель `--name`
+    pub const fn switch(name: &'static str) -> Self {
+        Self { name, short: None, takes_value: false }
+    }
+
+    /// Возвращает флаг с коротким именем `-short`
+    pub const fn short(self, short: &'static str) -> Self {
+        Self { short: Some(short), ..self }
+    }
+}
+
+/// Разобранные флаги подкоманды
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CliArgs {
+    values: HashMap<&'static str, String>,
+}
+
+impl CliArgs {
+    /// Функция разбора флагов подкоманды
+    ///
+    /// Флаги задаются в виде `--name value`, `--name=value` или `-short value`,
+    /// при повторе флага используется последнее значение.
+    ///
+    /// # Аргументы
+    ///
+    /// * args: &[String] - аргументы после имени подкоманды
+    /// * flags: &[Flag] - допустимые флаги
+    ///
+    /// # Ошибки
+    /// ParserError::InvalidArgs - неизвестный флаг, лишний аргумент или флаг без значения.
+    ///
+    /// # Пример
+    /// ```ignore
+    /// const FLAGS: [Flag; 2] = [Flag::value("input").short("i"), Flag::value("from")];
+    /// let args = CliArgs::parse(&env::args().skip(2).collect::<Vec<String>>(), &FLAGS)?;
+    /// let file_name = args.required("input")?;
+    /// ```
+    pub fn parse(args: &[String], flags: &[Flag]) -> Result<Self, ParserError> {
+        let mut result = Self::default();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let (key, inline) = match arg.split_once('=') {
+                Some((key, val)) if key.starts_with("--") => (key, Some(val.to_owned())),
+                _ => (arg.as_str(), None),
+            };
+            let flag = flags
+                .iter()
+                .find(|flag| {
+                    key.strip_prefix("--") == Some(flag.name)
+                        || (flag.short.is_some() && key.strip_prefix('-') == flag.short)
+                })
+                .ok_or_else(|| ParserError::InvalidArgs(format!("Unexpected argument: {}", arg)))?;
+
+            let val = match (flag.takes_value, inline) {
+                (true, Some(val)) => val,
+                (true, None) => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| ParserError::InvalidArgs(format!("Flag --{} requires a value", flag.name)))?,
+                (false, None) => String::new(),
+                (false, Some(_)) => {
+                    return Err(ParserError::InvalidArgs(format!("Flag --{} does not take a value", flag.name)));
+                }
+            };
+            result.values.insert(flag.name, val);
+        }
+        Ok(result)
+    }
+
+    /// Возвращает значение флага, None - флаг не задан
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|val| val.trim())
+    }
+
+    /// Возвращает true, если флаг задан
+    pub fn is_set(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Возвращает значение обязательного флага
+    ///
+    /// # Ошибки
+    /// ParserError::InvalidArgs - флаг не задан.
+    pub fn required(&self, name: &str) -> Result<&str, ParserError> {
+        self.value(name).ok_or_else(|| ParserError::InvalidArgs(format!("Missing required flag --{}", name)))
+    }
+
+    /// Возвращает значение флага, преобразованное к типу T, None - флаг не задан
+    ///
+    /// # Ошибки
+    /// ParserError::InvalidArgs - значение не преобразуется к типу T.
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Result<Option<T>, ParserError> {
+        self.value(name)
+            .map(|val| {
+                val.parse::<T>()
+                    .map_err(|_| ParserError::InvalidArgs(format!("Invalid value of --{}: {}", name, val)))
+            })
+            .transpose()
+    }
+
+    /// Возвращает формат файла file_name: значение флага name или расширение файла
+    ///
+    /// # Ошибки
+    /// ParserError::InvalidFormat - формат не поддерживается или не определяется по расширению.
+    pub fn format(&self, name: &str, file_name: &str) -> Result<TransactionsFormatType, ParserError> {
+        let val = match self.value(name) {
+            Some(val) => val,
+            None => Path::new(file_name).extension().and_then(|ext| ext.to_str()).unwrap_or_default(),
+        };
+        match get_format_value(&val.to_ascii_lowercase()) {
+            TransactionsFormatType::UNKNOWN => Err(ParserError::InvalidFormat),
+            format => Ok(format),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAGS: [Flag; 4] = [
+        Flag::value("input").short("i"),
+        Flag::value("from"),
+        Flag::value("run-size"),
+        Flag::switch("ordered"),
+    ];
+
+    fn args(vals: &[&str]) -> Vec<String> {
+        vals.iter().map(|val| val.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() -> Result<(), ParserError> {
+        let parsed = CliArgs::parse(&args(&["-i", "in.CSV", "--ordered", "--run-size=10"]), &FLAGS)?;
+        assert_eq!(parsed.required("input")?, "in.CSV");
+        assert!(parsed.is_set("ordered"));
+        assert_eq!(parsed.parsed::<usize>("run-size")?, Some(10));
+        assert_eq!(parsed.format("from", "in.CSV")?, TransactionsFormatType::CSV);
+        assert!(matches!(parsed.required("from"), Err(ParserError::InvalidArgs(_))));
+
+        let parsed = CliArgs::parse(&args(&["--input", "data.dat", "--from", "bin", "--run-size", "x"]), &FLAGS)?;
+        assert_eq!(parsed.format("from", "data.dat")?, TransactionsFormatType::BIN);
+        assert!(matches!(parsed.parsed::<usize>("run-size"), Err(ParserError::InvalidArgs(_))));
+        assert!(matches!(CliArgs::default().format("from", "data.dat"), Err(ParserError::InvalidFormat)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        for bad in [&["in.csv"][..], &["--input"], &["--unknown", "1"], &["--ordered=yes"], &["-x", "1"]] {
+            assert!(matches!(CliArgs::parse(&args(bad), &FLAGS), Err(ParserError::InvalidArgs(_))), "{:?}", bad);
+        }
+    }
+}
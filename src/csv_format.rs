@@ -1,11 +1,62 @@
 use crate::{
-    CVS_HEADER, ParserError, Transaction, TransactionsFormatType, TransactionsParser};
+    amount::AmountFormat,
+    CVS_HEADER, ParserError, Transaction, TransactionBuilder, TransactionsFormatType, TransactionsParser,
+    parse_amount_value, parse_status_value, parse_type_value, parse_u64_value, split_csv_line};
 
 #[derive(Default)]
 
 /// Парсер CSV формата
 pub struct CsvParser {
-    
+    allow_empty: bool,
+    amount_format: AmountFormat,
+}
+
+impl CsvParser {
+    /// Конструктор парсера в режиме обратной совместимости:
+    /// значения `EMPTY` типа и статуса транзакции допускаются при чтении
+    pub fn with_empty_compat() -> Self {
+        Self { allow_empty: true, ..Default::default() }
+    }
+
+    fn quoted_amount(&self, amount: u64) -> String {
+        let amount_str = self.amount_format.format(amount);
+        if amount_str.contains(',') {
+            "\"".to_owned() + amount_str.as_str() + "\""
+        }
+        else {
+            amount_str
+        }
+    }
+
+    /// Устанавливает формат записи сумм (по умолчанию - целые минорные единицы)
+    pub fn amount_format(mut self, amount_format: AmountFormat) -> Self {
+        self.amount_format = amount_format;
+        self
+    }
+
+    /// Функция чтения транзакции из строки формата CSV
+    /// * line - строка без заголовка
+    /// * numb - номер транзакции в наборе
+    pub(crate) fn parse_record(&self, line: &str, numb: usize) -> Result<Transaction, ParserError> {
+        let tmp_vec: Vec<&str> = split_csv_line(line);
+
+        if tmp_vec.len() != 8 {
+            return Result::Err(ParserError::InvalidCSVStructure(numb));
+        }
+
+        TransactionBuilder::new()
+            .allow_empty(self.allow_empty)
+            .tx_id(parse_u64_value(tmp_vec[0], numb)?)
+            .tx_type(parse_type_value(tmp_vec[1], numb)?)
+            .from_user_id(parse_u64_value(tmp_vec[2], numb)?)
+            .to_user_id(parse_u64_value(tmp_vec[3], numb)?)
+            .amount(parse_amount_value(tmp_vec[4], numb, &self.amount_format)?)
+            .timestamp(parse_u64_value(tmp_vec[5], numb)?)
+            .status(parse_status_value(tmp_vec[6], numb)?)
+            .description(serde_json::from_str::<String>(tmp_vec[7])?)
+            .build()
+            .map_err(|source| ParserError::InvalidTransaction { tx_numb: numb, source })
+    }
 }
 
 impl TransactionsParser for CsvParser {
@@ -27,33 +78,12 @@ impl TransactionsParser for CsvParser {
             .collect();
 
         for (numb, stx) in str_arr.iter().enumerate() {
-            let tmp_vec: Vec<&str> = stx
-            .split(",")
-            .map(|s| s.trim())
-            .collect();
-
-            if tmp_vec.len() != 8 {
-                return Result::Err(ParserError::InvalidCSVStructure(numb));
-            }
-
-            let str_tx = 
-                "{".to_owned() + 
-                "\"tx_id\":" + tmp_vec[0] + (",") + 
-                "\"tx_type\":" + "\"" + tmp_vec[1] + "\"" + (",") + 
-                "\"from_user_id\":" + tmp_vec[2] + (",") + 
-                "\"to_user_id\":" + tmp_vec[3] + (",") + 
-                "\"amount\":" + tmp_vec[4] + (",") + 
-                "\"timestamp\":" + tmp_vec[5] + (",") + 
-                "\"status\":" + "\"" + tmp_vec[6] + "\"" + (",") + 
-                "\"description\":" + tmp_vec[7] + 
-                "}";
-            let tx: Transaction = serde_json::from_str(&str_tx)?;
-            result.push(tx);
+            result.push(self.parse_record(stx, numb)?);
         }
         Ok(result) 
     }
 
-    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &Vec<Transaction>) -> Result<(), ParserError> {
+    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[Transaction]) -> Result<(), ParserError> {
         let mut result_str = String::from(CVS_HEADER);
 
         for tx in data {
@@ -62,7 +92,7 @@ impl TransactionsParser for CsvParser {
                 tx.tx_type.to_string().as_str() + "," +
                 tx.from_user_id.to_string().as_str() + "," +
                 tx.to_user_id.to_string().as_str() + "," +
-                tx.amount.to_string().as_str() + "," +
+                self.quoted_amount(tx.amount).as_str() + "," +
                 tx.timestamp.to_string().as_str() + "," +
                 tx.status.to_string().as_str() + "," +  
                 "\"" + tx.description.to_string().as_str() + "\""
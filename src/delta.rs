@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FieldChange, Transaction, TransactionField, diff::diff_tx_sets,
+    error::{ParserError, PatchError}, parse_status_value, parse_type_value, parse_u64_value};
+
+/// Запись файла изменений
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "op", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeltaEntry {
+    /// - ADD – добавленная запись
+    Add {
+        /// tx: Transaction - добавляемая запись
+        tx: Transaction,
+    },
+    /// - REMOVE – удаленная запись
+    Remove {
+        /// tx: Transaction - удаляемая запись
+        tx: Transaction,
+    },
+    /// - MODIFY – измененные поля записи с tx_id
+    Modify {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+        /// changes: Vec<FieldChange> - исходные и новые значения полей
+        changes: Vec<FieldChange>,
+    },
+}
+
+impl DeltaEntry {
+    /// Возвращает идентификатор транзакции записи изменений
+    pub fn tx_id(&self) -> u64 {
+        match self {
+            Self::Add { tx } | Self::Remove { tx } => tx.tx_id,
+            Self::Modify { tx_id, .. } => *tx_id,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Remove { .. } => 0,
+            Self::Modify { .. } => 1,
+            Self::Add { .. } => 2,
+        }
+    }
+}
+
+/// Парсер файла изменений (JSON Lines: одна запись изменений в строке)
+#[derive(Default)]
+pub struct DeltaParser {
+
+}
+
+impl DeltaParser {
+    /// Функция чтения записей изменений из источника
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_read<R: std::io::Read>(&self, source: &mut R) -> Result<Vec<DeltaEntry>, ParserError> {
+        let mut str_records = String::new();
+        source.read_to_string(&mut str_records)?;
+
+        str_records
+            .split("\n")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| Ok(serde_json::from_str::<DeltaEntry>(s)?))
+            .collect()
+    }
+
+    /// Функция записи записей изменений в источник
+    /// * target - получатель данных реализуйщий трейт Write (файл, буфер),
+    /// * data - записи изменений
+    pub fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[DeltaEntry]) -> Result<(), ParserError> {
+        let mut result_str = String::new();
+
+        for entry in data {
+            result_str += serde_json::to_string(entry)?.as_str();
+            result_str += "\n";
+        }
+
+        target.write_all(result_str.as_bytes())?;
+        target.flush()?;
+        Ok(())
+    }
+}
+
+/// Функция формирования изменений между наборами транзакций
+///
+/// Записи сопоставляются по tx_id (см. [`diff_tx_sets`]). Для записи, tx_id которой единственный
+/// в базовом наборе, формируется MODIFY только с измененными полями, для повторяющихся tx_id -
+/// REMOVE исходной и ADD новой записи.
+///
+/// # Аргументы
+///
+/// * base: &[Transaction] - базовый набор транзакций
+/// * target: &[Transaction] - новый набор транзакций
+///
+/// # Возвращаемое значение
+/// Записи изменений, упорядоченные по tx_id (для одного tx_id - REMOVE, MODIFY, ADD)
+///
+/// # Пример
+/// ```ignore
+/// let delta = make_delta(&yesterday_vec, &today_vec);
+/// DeltaParser::default().write_to(&mut delta_file, &delta)?;
+/// ```
+pub fn make_delta(base: &[Transaction], target: &[Transaction]) -> Vec<DeltaEntry> {
+    let mut base_counts: HashMap<u64, usize> = HashMap::new();
+    for tx in base {
+        *base_counts.entry(tx.tx_id).or_default() += 1;
+    }
+
+    let report = diff_tx_sets(base, target);
+    let mut delta: Vec<DeltaEntry> = Vec::new();
+    delta.extend(report.only_left.into_iter().map(|tx| DeltaEntry::Remove { tx }));
+    delta.extend(report.only_right.into_iter().map(|tx| DeltaEntry::Add { tx }));
+    for changed in report.changed {
+        if base_counts.get(&changed.tx_id) == Some(&1) {
+            delta.push(DeltaEntry::Modify { tx_id: changed.tx_id, changes: changed.changes });
+        } else {
+            delta.push(DeltaEntry::Remove { tx: changed.left });
+            delta.push(DeltaEntry::Add { tx: changed.right });
+        }
+    }
+
+    delta.sort_by_key(|entry| (entry.tx_id(), entry.rank()));
+    delta
+}
+
+/// Функция применения изменений к базовому набору транзакций
+///
+/// Записи изменений применяются по порядку: REMOVE удаляет одно вхождение совпадающей записи,
+/// MODIFY изменяет поля единственной записи с tx_id после проверки исходных значений,
+/// ADD добавляет запись в конец набора. Порядок остальных записей сохраняется.
+///
+/// # Аргументы
+///
+/// * base: &[Transaction] - базовый набор транзакций
+/// * delta: &[DeltaEntry] - записи изменений
+///
+/// # Возвращаемое значение
+/// Новый набор транзакций
+///
+/// # Ошибки
+/// ParserError::Patch - изменения не соответствуют базовому набору,
+/// ParserError::InvalidTransaction, ParserError::InvalidValue - некорректное новое значение поля
+/// (номер транзакции - номер записи изменений).
+pub fn apply_delta(base: &[Transaction], delta: &[DeltaEntry]) -> Result<Vec<Transaction>, ParserError> {
+    let mut slots: Vec<Option<Transaction>> = base.iter().cloned().map(Some).collect();
+    let mut positions: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (idx, tx) in base.iter().enumerate() {
+        positions.entry(tx.tx_id).or_default().push(idx);
+    }
+
+    for (numb, entry) in delta.iter().enumerate() {
+        let tx_id = entry.tx_id();
+        let found = positions.entry(tx_id).or_default();
+        match entry {
+            DeltaEntry::Add { tx } => {
+                found.push(slots.len());
+                slots.push(Some(tx.clone()));
+            }
+            DeltaEntry::Remove { tx } => {
+                let pos = found
+                    .iter()
+                    .position(|idx| slots[*idx].as_ref() == Some(tx))
+                    .ok_or(PatchError::MissingRecord { tx_id })?;
+                slots[found.remove(pos)] = None;
+            }
+            DeltaEntry::Modify { changes, .. } => {
+                let idx = match found.as_slice() {
+                    [idx] => *idx,
+                    [] => return Err(PatchError::MissingRecord { tx_id }.into()),
+                    _ => return Err(PatchError::AmbiguousRecord { tx_id }.into()),
+                };
+                let Some(tx) = slots[idx].as_mut() else { continue };
+                for change in changes {
+                    let actual = change.field.value_of(tx);
+                    if actual != change.before {
+                        return Err(PatchError::Mismatch { tx_id, field: change.field,
+                            expected: change.before.clone(), actual }.into());
+                    }
+                    set_field(tx, change.field, &change.after, numb)?;
+                }
+                if tx.tx_id != tx_id {
+                    found.clear();
+                    positions.entry(tx.tx_id).or_default().push(idx);
+                }
+            }
+        }
+    }
+
+    Ok(slots.into_iter().flatten().collect())
+}
+
+fn set_field(tx: &mut Transaction, field: TransactionField, val: &str, numb: usize) -> Result<(), ParserError> {
+    match field {
+        TransactionField::TxId => tx.tx_id = parse_u64_value(val, numb)?,
+        TransactionField::TxType => tx.tx_type = parse_type_value(val, numb)?,
+        TransactionField::FromUserId => tx.from_user_id = parse_u64_value(val, numb)?,
+        TransactionField::ToUserId => tx.to_user_id = parse_u64_value(val, numb)?,
+        TransactionField::Amount => tx.amount = parse_u64_value(val, numb)?,
+        TransactionField::Timestamp => tx.timestamp = parse_u64_value(val, numb)?,
+        TransactionField::Status => tx.status = parse_status_value(val, numb)?,
+        TransactionField::Description => tx.description = val.to_owned(),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, compare_tx_sets, test_tx};
+
+    #[test]
+    fn test_make_and_apply_delta() -> Result<(), Box<dyn std::error::Error>> {
+        let base = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 100).description("d").build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 200).description("d").build()?,
+        ];
+        let target = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 150).description("b, \"new\"").build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 300).description("d").build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 200).description("d").build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 100).description("e").build()?,
+        ];
+
+        let delta = make_delta(&base, &target);
+        assert_eq!(delta, vec![
+            DeltaEntry::Modify { tx_id: 2, changes: vec![
+                FieldChange { field: TransactionField::Amount, before: "100".to_owned(), after: "150".to_owned() },
+                FieldChange { field: TransactionField::Description, before: "b".to_owned(),
+                    after: "b, \"new\"".to_owned() },
+            ] },
+            DeltaEntry::Remove { tx: test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()? },
+            DeltaEntry::Remove { tx: test_tx(4, TransactionType::DEPOSIT, 0, 10, 100).description("d").build()? },
+            DeltaEntry::Add { tx: test_tx(4, TransactionType::DEPOSIT, 0, 10, 300).description("d").build()? },
+            DeltaEntry::Add { tx: test_tx(5, TransactionType::DEPOSIT, 0, 10, 100).description("e").build()? },
+        ]);
+
+        let mut buf: Vec<u8> = Vec::new();
+        DeltaParser::default().write_to(&mut buf, &delta)?;
+        let delta = DeltaParser::default().from_read(&mut buf.as_slice())?;
+
+        let patched = apply_delta(&base, &delta)?;
+        assert!(compare_tx_sets(patched, target));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_delta_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let base = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()?,
+        ];
+        let modify = |tx_id: u64| DeltaEntry::Modify { tx_id, changes: vec![
+            FieldChange { field: TransactionField::Amount, before: "90".to_owned(), after: "150".to_owned() },
+        ] };
+
+        assert!(matches!(apply_delta(&base, &[modify(1)]),
+            Err(ParserError::Patch(PatchError::Mismatch { tx_id: 1, field: TransactionField::Amount, .. }))));
+        assert!(matches!(apply_delta(&base, &[modify(2)]),
+            Err(ParserError::Patch(PatchError::AmbiguousRecord { tx_id: 2 }))));
+        let missing = test_tx(1, TransactionType::DEPOSIT, 0, 10, 200).description("a").build()?;
+        assert!(matches!(apply_delta(&base, &[DeltaEntry::Remove { tx: missing }]),
+            Err(ParserError::Patch(PatchError::MissingRecord { tx_id: 1 }))));
+        Ok(())
+    }
+}
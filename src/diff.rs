@@ -0,0 +1,476 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FieldChange, Transaction, TransactionField, error::ParserError};
+
+/// Пара записей с одинаковым tx_id и различающимися полями
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ChangedTransaction {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - left – запись первого набора
+    pub left: Transaction,
+    /// - right – запись второго набора
+    pub right: Transaction,
+    /// - changes – различающиеся поля
+    pub changes: Vec<FieldChange>,
+}
+
+/// Результат сравнения двух наборов транзакций
+#[derive(Serialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct DiffReport {
+    /// - only_left – записи, отсутствующие во втором наборе
+    pub only_left: Vec<Transaction>,
+    /// - only_right – записи, отсутствующие в первом наборе
+    pub only_right: Vec<Transaction>,
+    /// - changed – записи с совпадающим tx_id и различающимися полями
+    pub changed: Vec<ChangedTransaction>,
+}
+
+impl DiffReport {
+    /// Возвращает true, если наборы совпадают
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+
+    /// Возвращает счетчики отчета для наборов из left_total и right_total записей
+    pub fn counts(&self, left_total: usize, right_total: usize) -> DiffCounts {
+        DiffCounts {
+            left_total,
+            right_total,
+            only_left: self.only_left.len(),
+            only_right: self.only_right.len(),
+            changed: self.changed.len(),
+        }
+    }
+}
+
+/// Счетчики результата сравнения
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DiffCounts {
+    /// - left_total – число записей первого набора
+    pub left_total: usize,
+    /// - right_total – число записей второго набора
+    pub right_total: usize,
+    /// - only_left – число записей, отсутствующих во втором наборе
+    pub only_left: usize,
+    /// - only_right – число записей, отсутствующих в первом наборе
+    pub only_right: usize,
+    /// - changed – число пар записей с различающимися полями
+    pub changed: usize,
+}
+
+#[derive(Serialize)]
+struct DiffDocument<'a> {
+    equal: bool,
+    counts: DiffCounts,
+    #[serde(flatten)]
+    report: &'a DiffReport,
+}
+
+/// Функция записи результата сравнения в формате JSON
+///
+/// Документ содержит признак совпадения наборов (equal), счетчики (counts)
+/// и списки only_left, only_right, changed.
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер, stdout)
+/// * report - результат сравнения
+/// * left_total - число записей первого набора
+/// * right_total - число записей второго набора
+pub fn write_diff_json<W: std::io::Write>(target: &mut W, report: &DiffReport, left_total: usize,
+    right_total: usize) -> Result<(), ParserError> {
+
+    let document = DiffDocument { equal: report.is_empty(), counts: report.counts(left_total, right_total), report };
+    serde_json::to_writer_pretty(&mut *target, &document)?;
+    target.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Расхождение числа повторов записи в наборах
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct MultiplicityDiff {
+    /// - tx – запись
+    pub tx: Transaction,
+    /// - left_count – число повторов записи в первом наборе
+    pub left_count: usize,
+    /// - right_count – число повторов записи во втором наборе
+    pub right_count: usize,
+}
+
+/// Функция сравнения наборов транзакций как мультимножеств
+///
+/// # Аргументы
+///
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+///
+/// # Возвращаемое значение
+/// Записи, число повторов которых в наборах различается (в том числе отсутствующие в одном из наборов),
+/// упорядоченные по tx_id. Пустой список - наборы совпадают.
+pub fn multiplicity_diff(left_side: &[Transaction], right_side: &[Transaction]) -> Vec<MultiplicityDiff> {
+    let mut counts: BTreeMap<&Transaction, (usize, usize)> = BTreeMap::new();
+    for tx in left_side {
+        counts.entry(tx).or_default().0 += 1;
+    }
+    for tx in right_side {
+        counts.entry(tx).or_default().1 += 1;
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, (left_count, right_count))| left_count != right_count)
+        .map(|(tx, (left_count, right_count))| MultiplicityDiff { tx: tx.clone(), left_count, right_count })
+        .collect()
+}
+
+/// Правила сравнения наборов транзакций
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(default)]
+pub struct CompareOptions {
+    /// - key_fields – поля, по которым сопоставляются различающиеся записи
+    pub key_fields: Vec<TransactionField>,
+    /// - ignore_fields – поля, не участвующие в сравнении
+    pub ignore_fields: Vec<TransactionField>,
+    /// - timestamp_tolerance_ms – допустимое расхождение времени транзакций, мс
+    pub timestamp_tolerance_ms: u64,
+    /// - ignore_case – сравнение описаний без учета регистра
+    pub ignore_case: bool,
+    /// - normalize_whitespace – сравнение описаний без учета начальных, конечных и повторных пробелов
+    pub normalize_whitespace: bool,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            key_fields: vec![TransactionField::TxId],
+            ignore_fields: Vec::new(),
+            timestamp_tolerance_ms: 0,
+            ignore_case: false,
+            normalize_whitespace: false,
+        }
+    }
+}
+
+impl CompareOptions {
+    /// Функция получения списка различающихся полей двух транзакций по правилам сравнения
+    ///
+    /// # Возвращаемое значение
+    /// Список изменений полей (исходные значения) в порядке следования полей в формате CSV,
+    /// без игнорируемых полей и расхождений в пределах допусков
+    pub fn field_changes(&self, tx_left: &Transaction, tx_right: &Transaction) -> Vec<FieldChange> {
+        self.compared_fields()
+            .filter(|field| !self.field_matches(*field, tx_left, tx_right))
+            .map(|field| FieldChange { field, before: field.value_of(tx_left), after: field.value_of(tx_right) })
+            .collect()
+    }
+
+    /// Возвращает true, если записи совпадают по правилам сравнения
+    pub fn is_match(&self, tx_left: &Transaction, tx_right: &Transaction) -> bool {
+        self.compared_fields().all(|field| self.field_matches(field, tx_left, tx_right))
+    }
+
+    fn field_matches(&self, field: TransactionField, tx_left: &Transaction, tx_right: &Transaction) -> bool {
+        match field {
+            TransactionField::TxId => tx_left.tx_id == tx_right.tx_id,
+            TransactionField::TxType => tx_left.tx_type == tx_right.tx_type,
+            TransactionField::FromUserId => tx_left.from_user_id == tx_right.from_user_id,
+            TransactionField::ToUserId => tx_left.to_user_id == tx_right.to_user_id,
+            TransactionField::Amount => tx_left.amount == tx_right.amount,
+            TransactionField::Timestamp => self.timestamps_match(tx_left, tx_right),
+            TransactionField::Status => tx_left.status == tx_right.status,
+            TransactionField::Description => self.descriptions_match(&tx_left.description, &tx_right.description),
+        }
+    }
+
+    /// Сравнение описаний по правилам без построения нормализованных строк
+    fn descriptions_match(&self, left: &str, right: &str) -> bool {
+        let same = |left: &str, right: &str| match self.ignore_case {
+            true => left.chars().flat_map(char::to_lowercase).eq(right.chars().flat_map(char::to_lowercase)),
+            false => left == right,
+        };
+        if !self.normalize_whitespace {
+            return same(left, right);
+        }
+
+        let (mut left_words, mut right_words) = (left.split_whitespace(), right.split_whitespace());
+        loop {
+            match (left_words.next(), right_words.next()) {
+                (None, None) => return true,
+                (Some(left), Some(right)) if same(left, right) => continue,
+                _ => return false,
+            }
+        }
+    }
+
+    fn compared_fields(&self) -> impl Iterator<Item = TransactionField> + '_ {
+        TransactionField::ALL.into_iter().filter(|field| !self.ignore_fields.contains(field))
+    }
+
+    fn value_of(&self, field: TransactionField, tx: &Transaction) -> String {
+        let mut val = field.value_of(tx);
+        if field == TransactionField::Description {
+            if self.normalize_whitespace {
+                val = val.split_whitespace().collect::<Vec<&str>>().join(" ");
+            }
+            if self.ignore_case {
+                val = val.to_lowercase();
+            }
+        }
+        val
+    }
+
+    fn timestamps_match(&self, tx_left: &Transaction, tx_right: &Transaction) -> bool {
+        self.ignore_fields.contains(&TransactionField::Timestamp)
+            || tx_left.timestamp.abs_diff(tx_right.timestamp) <= self.timestamp_tolerance_ms
+    }
+
+    /// Значения сравниваемых полей, кроме времени (время сравнивается с допуском)
+    fn content_key(&self, tx: &Transaction) -> Vec<String> {
+        self.compared_fields()
+            .filter(|field| *field != TransactionField::Timestamp)
+            .map(|field| self.value_of(field, tx))
+            .collect()
+    }
+
+    fn pair_key(&self, tx: &Transaction) -> Vec<String> {
+        self.key_fields.iter().map(|field| self.value_of(*field, tx)).collect()
+    }
+}
+
+/// Функция сравнения двух наборов транзакций с детализацией по полям
+///
+/// Сравнение с правилами по умолчанию, см. [`diff_tx_sets_with`].
+///
+/// # Аргументы
+///
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+///
+/// # Возвращаемое значение
+/// DiffReport, списки упорядочены по tx_id
+///
+/// # Пример
+/// ```ignore
+/// let report = diff_tx_sets(&left_vec, &right_vec);
+/// for changed in &report.changed {
+///     println!("tx {}: {} fields differ", changed.tx_id, changed.changes.len());
+/// }
+/// ```
+pub fn diff_tx_sets(left_side: &[Transaction], right_side: &[Transaction]) -> DiffReport {
+    diff_tx_sets_with(left_side, right_side, &CompareOptions::default())
+}
+
+/// Функция сравнения двух наборов транзакций по правилам сравнения
+///
+/// Сначала попарно исключаются записи, совпадающие по правилам сравнения, с учетом числа повторов
+/// (при допуске по времени число исключенных пар максимально), затем оставшиеся записи
+/// сопоставляются по ключевым полям в порядке следования в наборах.
+/// Записи без пары попадают в only_left или only_right.
+///
+/// # Аргументы
+///
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+/// * options: &CompareOptions - правила сравнения
+///
+/// # Возвращаемое значение
+/// DiffReport, списки упорядочены по tx_id (changed - по tx_id записи первого набора)
+///
+/// # Пример
+/// ```ignore
+/// let options = CompareOptions { timestamp_tolerance_ms: 5, normalize_whitespace: true, ..Default::default() };
+/// let report = diff_tx_sets_with(&our_vec, &partner_vec, &options);
+/// ```
+pub fn diff_tx_sets_with(left_side: &[Transaction], right_side: &[Transaction], options: &CompareOptions) -> DiffReport {
+    let (left_rest, right_rest) = unmatched(left_side, right_side, options);
+
+    let mut left_by_key: BTreeMap<Vec<String>, Vec<&Transaction>> = BTreeMap::new();
+    for tx in left_rest {
+        left_by_key.entry(options.pair_key(tx)).or_default().push(tx);
+    }
+    let mut right_by_key: BTreeMap<Vec<String>, Vec<&Transaction>> = BTreeMap::new();
+    for tx in right_rest {
+        right_by_key.entry(options.pair_key(tx)).or_default().push(tx);
+    }
+
+    let mut report = DiffReport::default();
+    for (key, left_txs) in left_by_key {
+        let right_txs = right_by_key.remove(&key).unwrap_or_default();
+        for (left, right) in left_txs.iter().zip(right_txs.iter()) {
+            report.changed.push(ChangedTransaction {
+                tx_id: left.tx_id,
+                left: (*left).clone(),
+                right: (*right).clone(),
+                changes: options.field_changes(left, right),
+            });
+        }
+        report.only_left.extend(left_txs.iter().skip(right_txs.len()).map(|tx| (*tx).clone()));
+        report.only_right.extend(right_txs.iter().skip(left_txs.len()).map(|tx| (*tx).clone()));
+    }
+    for right_txs in right_by_key.into_values() {
+        report.only_right.extend(right_txs.into_iter().cloned());
+    }
+
+    report.only_left.sort();
+    report.only_right.sort();
+    report.changed.sort_by(|a, b| a.left.cmp(&b.left));
+    report
+}
+
+/// Исключает из наборов записи, совпадающие по правилам сравнения, с учетом числа повторов,
+/// возвращает оставшиеся записи в порядке следования
+fn unmatched<'a>(left_side: &'a [Transaction], right_side: &'a [Transaction],
+    options: &CompareOptions) -> (Vec<&'a Transaction>, Vec<&'a Transaction>) {
+
+    let mut groups: HashMap<Vec<String>, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    for (pos, tx) in left_side.iter().enumerate() {
+        groups.entry(options.content_key(tx)).or_default().0.push(pos);
+    }
+    for (pos, tx) in right_side.iter().enumerate() {
+        groups.entry(options.content_key(tx)).or_default().1.push(pos);
+    }
+
+    let mut left_matched = vec![false; left_side.len()];
+    let mut right_matched = vec![false; right_side.len()];
+    for (mut left_pos, mut right_pos) in groups.into_values() {
+        // Жадное сопоставление упорядоченных по времени записей дает максимальное число пар
+        left_pos.sort_by_key(|pos| left_side[*pos].timestamp);
+        right_pos.sort_by_key(|pos| right_side[*pos].timestamp);
+        let (mut i, mut j) = (0, 0);
+        while i < left_pos.len() && j < right_pos.len() {
+            let (left, right) = (&left_side[left_pos[i]], &right_side[right_pos[j]]);
+            if options.timestamps_match(left, right) {
+                left_matched[left_pos[i]] = true;
+                right_matched[right_pos[j]] = true;
+                i += 1;
+                j += 1;
+            } else if left.timestamp < right.timestamp {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    let left_rest = left_side.iter().zip(left_matched).filter(|(_, matched)| !matched).map(|(tx, _)| tx).collect();
+    let right_rest = right_side.iter().zip(right_matched).filter(|(_, matched)| !matched).map(|(tx, _)| tx).collect();
+    (left_rest, right_rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    #[test]
+    fn test_diff_tx_sets() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()?,
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 1).description("e").build()?,
+        ];
+        let right = vec![
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 100).description("d").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 150).description("a").build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()?,
+        ];
+
+        let report = diff_tx_sets(&left, &right);
+        assert_eq!(report.only_left, vec![
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 1).description("e").build()?,
+        ]);
+        assert_eq!(report.only_right, vec![
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 100).description("d").build()?,
+        ]);
+        assert_eq!(report.changed, vec![ChangedTransaction {
+            tx_id: 1,
+            left: test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            right: test_tx(1, TransactionType::DEPOSIT, 0, 10, 150).description("a").build()?,
+            changes: vec![FieldChange { field: TransactionField::Amount, before: "100".to_owned(),
+                after: "150".to_owned() }],
+        }]);
+
+        assert!(diff_tx_sets(&left, &left).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiplicity_diff() -> Result<(), Box<dyn std::error::Error>> {
+        let a = test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?;
+        let b = test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?;
+        let left = vec![a.clone(), a.clone(), b.clone()];
+        let right = vec![b.clone(), a.clone(), b.clone()];
+        assert_eq!(multiplicity_diff(&left, &right), vec![
+            MultiplicityDiff { tx: a.clone(), left_count: 2, right_count: 1 },
+            MultiplicityDiff { tx: b.clone(), left_count: 1, right_count: 2 },
+        ]);
+        assert!(multiplicity_diff(&left, &[b, a.clone(), a]).is_empty());
+
+        let report = diff_tx_sets(&left, &right);
+        assert_eq!((report.only_left.len(), report.only_right.len()), (1, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_with_options() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).timestamp(1000).description("Salary  payment").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).timestamp(2000).description("b").build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).timestamp(3000).description("c").build()?,
+        ];
+        let right = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).timestamp(1004).description(" salary payment").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).timestamp(2010).description("b").build()?,
+            test_tx(30, TransactionType::DEPOSIT, 0, 10, 100).timestamp(3000).description("c").build()?,
+        ];
+
+        let strict = diff_tx_sets(&left, &right);
+        assert_eq!(strict.changed.len(), 2);
+
+        let options: CompareOptions = serde_json::from_str(r#"{"timestamp_tolerance_ms": 5,
+            "ignore_case": true, "normalize_whitespace": true}"#)?;
+        let report = diff_tx_sets_with(&left, &right, &options);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(report.changed[0].changes, vec![FieldChange { field: TransactionField::Timestamp,
+            before: "2000".to_owned(), after: "2010".to_owned() }]);
+        assert_eq!((report.only_left.len(), report.only_right.len()), (1, 1));
+
+        let by_content = CompareOptions {
+            key_fields: vec![TransactionField::Description],
+            ignore_fields: vec![TransactionField::TxId],
+            ..options
+        };
+        let report = diff_tx_sets_with(&left, &right, &by_content);
+        assert_eq!(report.changed.len(), 1);
+        assert!(report.only_left.is_empty() && report.only_right.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_diff_json() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+        ];
+        let right = vec![test_tx(1, TransactionType::DEPOSIT, 0, 10, 150).description("a").build()?];
+        let report = diff_tx_sets(&left, &right);
+
+        let mut buf: Vec<u8> = Vec::new();
+        write_diff_json(&mut buf, &report, left.len(), right.len())?;
+        let json: serde_json::Value = serde_json::from_slice(&buf)?;
+        assert_eq!(json["equal"], false);
+        assert_eq!(json["counts"], serde_json::json!({"left_total": 2, "right_total": 1, "only_left": 1,
+            "only_right": 0, "changed": 1}));
+        assert_eq!(json["only_left"][0]["tx_id"], 2);
+        assert_eq!(json["changed"][0]["changes"][0], serde_json::json!({"field": "AMOUNT", "before": "100",
+            "after": "150"}));
+        Ok(())
+    }
+}
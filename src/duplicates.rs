@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::{FieldChange, Transaction, TransactionType, diff_fields};
+
+/// Окно поиска повторов по умолчанию, мс
+pub const DEFAULT_REPLAY_WINDOW_MS: u64 = 1000;
+
+/// Полностью совпадающие записи
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExactDuplicate {
+    /// - tx – повторяющаяся запись
+    pub tx: Transaction,
+    /// - positions – номера всех вхождений записи в наборе
+    pub positions: Vec<usize>,
+}
+
+/// Записи с одинаковым tx_id и различающимся содержимым
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IdConflict {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - first – номер первой записи с этим tx_id
+    pub first: usize,
+    /// - other – номер конфликтующей записи
+    pub other: usize,
+    /// - changes – отличия конфликтующей записи от первой
+    pub changes: Vec<FieldChange>,
+}
+
+/// Вероятный повтор транзакции под другим tx_id:
+/// совпадают тип, участники и сумма, время отличается не более чем на окно поиска
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProbableReplay {
+    /// - original_tx_id – идентификатор более ранней транзакции
+    pub original_tx_id: u64,
+    /// - replay_tx_id – идентификатор вероятного повтора
+    pub replay_tx_id: u64,
+    /// - delta_ms – разница во времени, мс
+    pub delta_ms: u64,
+}
+
+/// Результат поиска дубликатов
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct DuplicatesReport {
+    /// - exact – полностью совпадающие записи
+    pub exact: Vec<ExactDuplicate>,
+    /// - conflicts – записи с одинаковым tx_id и различающимся содержимым
+    pub conflicts: Vec<IdConflict>,
+    /// - replays – вероятные повторы под другим tx_id
+    pub replays: Vec<ProbableReplay>,
+}
+
+impl DuplicatesReport {
+    /// Возвращает true, если дубликаты не найдены
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.conflicts.is_empty() && self.replays.is_empty()
+    }
+}
+
+/// Функция поиска дубликатов и конфликтов tx_id в наборе транзакций
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - набор транзакций
+/// * replay_window_ms: u64 - максимальная разница во времени для вероятного повтора, мс
+///
+/// # Возвращаемое значение
+/// DuplicatesReport, элементы которого упорядочены по первому вхождению в набор
+///
+/// # Пример
+/// ```ignore
+/// let report = find_duplicates(&tx_vec, DEFAULT_REPLAY_WINDOW_MS);
+/// if !report.is_empty() {
+///     println!("{} conflicts", report.conflicts.len());
+/// }
+/// ```
+pub fn find_duplicates(data: &[Transaction], replay_window_ms: u64) -> DuplicatesReport {
+    let mut report = DuplicatesReport::default();
+
+    let mut exact_index: HashMap<&Transaction, usize> = HashMap::new();
+    let mut distinct: Vec<(usize, &Transaction)> = Vec::new();
+    for (position, tx) in data.iter().enumerate() {
+        match exact_index.get(tx) {
+            Some(idx) => report.exact[*idx].positions.push(position),
+            None => {
+                exact_index.insert(tx, report.exact.len());
+                report.exact.push(ExactDuplicate { tx: tx.clone(), positions: vec![position] });
+                distinct.push((position, tx));
+            }
+        }
+    }
+    report.exact.retain(|duplicate| duplicate.positions.len() > 1);
+
+    let mut first_by_id: HashMap<u64, (usize, &Transaction)> = HashMap::new();
+    for (position, tx) in distinct {
+        match first_by_id.get(&tx.tx_id) {
+            Some((first, first_tx)) => report.conflicts.push(IdConflict {
+                tx_id: tx.tx_id,
+                first: *first,
+                other: position,
+                changes: diff_fields(first_tx, tx),
+            }),
+            None => {
+                first_by_id.insert(tx.tx_id, (position, tx));
+            }
+        }
+    }
+
+    let mut candidates: Vec<(usize, &Transaction)> = first_by_id.into_values().collect();
+    candidates.sort_by_key(|(position, tx)| (replay_key(tx), tx.timestamp, *position));
+
+    let mut replays: Vec<(usize, ProbableReplay)> = candidates
+        .windows(2)
+        .filter(|pair| replay_key(pair[0].1) == replay_key(pair[1].1))
+        .filter(|pair| pair[1].1.timestamp - pair[0].1.timestamp <= replay_window_ms)
+        .map(|pair| (pair[1].0, ProbableReplay {
+            original_tx_id: pair[0].1.tx_id,
+            replay_tx_id: pair[1].1.tx_id,
+            delta_ms: pair[1].1.timestamp - pair[0].1.timestamp,
+        }))
+        .collect();
+    replays.sort_by_key(|(position, _)| *position);
+    report.replays = replays.into_iter().map(|(_, replay)| replay).collect();
+
+    report
+}
+
+fn replay_key(tx: &Transaction) -> (&TransactionType, u64, u64, u64) {
+    (&tx.tx_type, tx.from_user_id, tx.to_user_id, tx.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionField, test_tx};
+
+    #[test]
+    fn test_find_duplicates() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 200).timestamp(10_000).build()?,
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 250).timestamp(10_000).build()?,
+            test_tx(3, TransactionType::TRANSFER, 10, 20, 100).timestamp(500).build()?,
+            test_tx(4, TransactionType::TRANSFER, 10, 20, 100).timestamp(5_000).build()?,
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?,
+        ];
+        let report = find_duplicates(&data, DEFAULT_REPLAY_WINDOW_MS);
+
+        assert_eq!(report.exact, vec![ExactDuplicate { tx: data[0].clone(), positions: vec![0, 2, 6] }]);
+        assert_eq!(report.conflicts, vec![IdConflict {
+            tx_id: 2,
+            first: 1,
+            other: 3,
+            changes: vec![FieldChange { field: TransactionField::Amount, before: "200".to_owned(), after: "250".to_owned() }],
+        }]);
+        assert_eq!(report.replays, vec![ProbableReplay { original_tx_id: 1, replay_tx_id: 3, delta_ms: 500 }]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_duplicates() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 100).timestamp(2_000).build()?,
+        ];
+        assert!(find_duplicates(&data, DEFAULT_REPLAY_WINDOW_MS).is_empty());
+        Ok(())
+    }
+}
@@ -1,17 +1,14 @@
 use thiserror::Error;
 use std::io;
 
+use crate::{TransactionField, TransactionType};
+
 /// Сообщение об ошибке чтения
 pub const ERR_READ_MSG: &str = "I\\O error while reading from data source";
 /// Сообщение об ошибке записи
 pub const ERR_WRITE_MSG: &str = "I\\O error while writing to data source";
 /// Сообщение об ошибке формата
 pub const ERR_FORMAT: &str = "Input or output format is incorrect. Use txt, csv or bin.";
-/// Сообщение об ошибке указания параметров при запуске утилиты ковертера
-pub const ERR_PARAMS_CONVERTER: &str = "Invalid args. Try to use:\n <input-filename> txt|csv|bin txt|csv|bin <output-filename>";
-/// Сообщение об ошибке указания параметров при запуске утилиты сравнения
-pub const ERR_PARAMS_COMPARER: &str = "Invalid args. Try to use:\n <first-filename> txt|csv|bin txt|csv|bin <second-filename>";
-
 
 /// Ошибки парсинга
 #[derive(Error, Debug)]
@@ -34,10 +31,185 @@ pub enum ParserError {
         /// tx_numb: usize - номер транзакции в наборе
         tx_numb: usize
     },
+    /// Ошибки построения транзакции (отсутствуют обязательные поля, неизвестный тип или статус)
+    #[error("Invalid transaction #{tx_numb}: {source}")]
+    InvalidTransaction {
+        /// tx_numb: usize - номер транзакции в наборе
+        tx_numb: usize,
+        /// source: BuildError - причина ошибки
+        source: BuildError,
+    },
+    /// Ошибки чтения суммы транзакции
+    #[error("Invalid amount in transaction #{tx_numb}: {source}")]
+    InvalidAmount {
+        /// tx_numb: usize - номер транзакции в наборе
+        tx_numb: usize,
+        /// source: AmountError - причина ошибки
+        source: AmountError,
+    },
+    /// Некорректное значение типа или состояния счета в реестре счетов
+    #[error("Invalid account type or status value: {0}")]
+    InvalidAccountValue(String),
+    /// Неизвестный способ группировки статистики
+    #[error("Unknown grouping: {0}. Use all, type, status, user, day or hour.")]
+    InvalidGrouping(String),
+    /// Неизвестное поле транзакции
+    #[error("Unknown transaction field: {0}")]
+    UnknownField(String),
+    /// Ошибки таблицы курсов и пересчета сумм
+    #[error("Currency conversion error: {0}")]
+    Conversion(#[from] FxError),
+    /// Ошибки применения файла изменений
+    #[error("Patch error: {0}")]
+    Patch(#[from] PatchError),
+    /// Некорректные аргументы командной строки (текст - описание ошибки)
+    #[error("{0}")]
+    InvalidArgs(String),
     /// Ошибки не соответствия формата
     #[error("Input or output format is incorrect. Use txt, csv or bin.")]
     InvalidFormat,
     /// Иные ошибки
     #[error("Unknown parsing error")]
     Unknown,
-}
\ No newline at end of file
+}
+
+/// Ошибки построения транзакции
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum BuildError {
+    /// Не задано обязательное поле
+    #[error("Required field {0} is missing")]
+    MissingField(&'static str),
+    /// Неизвестный тип транзакции
+    #[error("Unknown transaction type: {0}")]
+    UnknownType(String),
+    /// Неизвестный статус транзакции
+    #[error("Unknown transaction status: {0}")]
+    UnknownStatus(String),
+    /// Тип транзакции не определен (EMPTY)
+    #[error("Transaction type is not defined")]
+    UndefinedType,
+    /// Статус транзакции не определен (EMPTY)
+    #[error("Transaction status is not defined")]
+    UndefinedStatus,
+    /// Идентификатор создаваемой транзакции не помещается в u64
+    #[error("Transaction id is out of range")]
+    IdOverflow,
+}
+
+/// Ошибки чтения денежных сумм
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AmountError {
+    /// Некорректная запись суммы
+    #[error("Malformed amount: {0}")]
+    Malformed(String),
+    /// Сумма содержит больше разрядов дробной части, чем допускает формат, а округление запрещено
+    #[error("Amount {0} has more fractional digits than allowed")]
+    Precision(String),
+    /// Сумма не помещается в u64
+    #[error("Amount {0} is too large")]
+    Overflow(String),
+    /// Неизвестная локаль
+    #[error("Unknown locale: {0}")]
+    UnknownLocale(String),
+}
+
+/// Ошибки расчета балансов
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum LedgerError {
+    /// Переполнение баланса пользователя при применении транзакции
+    #[error("Balance overflow for user {user_id} in transaction {tx_id}")]
+    Overflow {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+        /// user_id: u64 - идентификатор пользователя
+        user_id: u64,
+    },
+}
+
+/// Ошибки расчета процентов и комиссий
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ChargeError {
+    /// Начисление переполняет баланс пользователя
+    #[error(transparent)]
+    Ledger(#[from] LedgerError),
+    /// Транзакция начисления не строится (в том числе идентификатор вне диапазона u64)
+    #[error("Invalid charge transaction: {0}")]
+    Build(#[from] BuildError),
+}
+
+/// Ошибки формирования журнала проводок
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum JournalError {
+    /// Для типа транзакции не задано правило проводки
+    #[error("No posting rule for transaction type {0}")]
+    MissingRule(TransactionType),
+    /// Журнал транзакции не сбалансирован
+    #[error("Journal of transaction {tx_id} is unbalanced: debit {debit}, credit {credit}")]
+    Unbalanced {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+        /// debit: u128 - сумма по дебету
+        debit: u128,
+        /// credit: u128 - сумма по кредиту
+        credit: u128,
+    },
+}
+
+/// Ошибки пересчета сумм по курсам валют
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum FxError {
+    /// Некорректная запись курса
+    #[error("Invalid rate: {0}")]
+    InvalidRate(String),
+    /// В таблице нет курса пары валют на момент транзакции
+    #[error("No {from}/{to} rate effective at {timestamp}")]
+    MissingRate {
+        /// from: String - код исходной валюты
+        from: String,
+        /// to: String - код валюты пересчета
+        to: String,
+        /// timestamp: u64 - время транзакции
+        timestamp: u64,
+    },
+    /// Пересчитанная сумма не выражается точно в минорных единицах, а округление запрещено
+    #[error("Converted amount of transaction {tx_id} requires rounding")]
+    Precision {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+    },
+    /// Пересчитанная сумма не помещается в u64
+    #[error("Converted amount of transaction {tx_id} is too large")]
+    Overflow {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+    },
+}
+
+/// Ошибки применения файла изменений к базовому набору
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum PatchError {
+    /// В базовом наборе нет изменяемой или удаляемой записи
+    #[error("Record {tx_id} to change is missing in the base set")]
+    MissingRecord {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+    },
+    /// В базовом наборе несколько записей с изменяемым tx_id
+    #[error("Record {tx_id} to modify is not unique in the base set")]
+    AmbiguousRecord {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+    },
+    /// Значение поля в базовом наборе отличается от исходного значения в файле изменений
+    #[error("Record {tx_id} field {field} is {actual}, expected {expected}")]
+    Mismatch {
+        /// tx_id: u64 - идентификатор транзакции
+        tx_id: u64,
+        /// field: TransactionField - поле транзакции
+        field: TransactionField,
+        /// expected: String - исходное значение в файле изменений
+        expected: String,
+        /// actual: String - значение в базовом наборе
+        actual: String,
+    },
+}
@@ -0,0 +1,397 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Lines, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    Transaction, TransactionField, TransactionsFormatType,
+    bin_format::BinParser,
+    csv_format::CsvParser,
+    diff::{CompareOptions, DiffReport, MultiplicityDiff, diff_tx_sets_with, multiplicity_diff},
+    error::ParserError,
+    txt_format::TxtParser,
+};
+
+/// Число записей в отсортированном фрагменте по умолчанию
+pub const DEFAULT_RUN_SIZE: usize = 1_000_000;
+
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Потоковое чтение транзакций из файла: записи читаются по одной, файл целиком в память не загружается
+pub struct TxReader {
+    format: TransactionsFormatType,
+    reader: BufReader<File>,
+    txt: TxtParser,
+    csv: CsvParser,
+    bin: BinParser,
+    numb: usize,
+    done: bool,
+}
+
+impl TxReader {
+    /// Конструктор потокового чтения файла
+    ///
+    /// # Ошибки
+    /// ParserError::InvalidFormat - формат не поддерживается, ParserError::Io - ошибка открытия файла.
+    pub fn open(file_name: &str, format: TransactionsFormatType) -> Result<Self, ParserError> {
+        if format == TransactionsFormatType::UNKNOWN {
+            return Err(ParserError::InvalidFormat);
+        }
+        Ok(Self {
+            format,
+            reader: BufReader::new(File::open(file_name.trim())?),
+            txt: TxtParser::default(),
+            csv: CsvParser::default(),
+            bin: BinParser::default(),
+            numb: 0,
+            done: false,
+        })
+    }
+
+    fn next_record(&mut self) -> Result<Option<Transaction>, ParserError> {
+        match self.format {
+            TransactionsFormatType::BIN => self.bin.read_record(&mut self.reader, self.numb),
+            TransactionsFormatType::CSV => {
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    if self.reader.read_line(&mut line)? == 0 {
+                        return Ok(None);
+                    }
+                    let trimmed = line.trim();
+                    if !trimmed.is_empty() && !trimmed.contains("TX_ID") {
+                        return self.csv.parse_record(trimmed, self.numb).map(Some);
+                    }
+                }
+            }
+            TransactionsFormatType::TXT => {
+                let (mut block, mut line) = (String::new(), String::new());
+                loop {
+                    line.clear();
+                    let eof = self.reader.read_line(&mut line)? == 0;
+                    if eof || line.trim().is_empty() {
+                        if !block.trim().is_empty() {
+                            return self.txt.parse_record(&block, self.numb).map(Some);
+                        }
+                        if eof {
+                            return Ok(None);
+                        }
+                        continue;
+                    }
+                    block += line.as_str();
+                }
+            }
+            TransactionsFormatType::UNKNOWN => Err(ParserError::InvalidFormat),
+        }
+    }
+}
+
+impl Iterator for TxReader {
+    type Item = Result<Transaction, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_record();
+        self.numb += 1;
+        match result {
+            Ok(Some(tx)) => Some(Ok(tx)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Параметры внешней сортировки
+#[derive(Debug, Clone)]
+pub struct ExternalSortOptions {
+    /// - run_size – число записей, сортируемых в памяти и сбрасываемых во временный файл
+    pub run_size: usize,
+    /// - temp_dir – каталог временных файлов
+    pub temp_dir: PathBuf,
+}
+
+impl Default for ExternalSortOptions {
+    fn default() -> Self {
+        Self { run_size: DEFAULT_RUN_SIZE, temp_dir: std::env::temp_dir() }
+    }
+}
+
+/// Временный файл фрагмента, удаляется при освобождении
+struct SpillFile(PathBuf);
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Запись фрагмента: транзакция и ее позиция во входном наборе
+type Positioned = (usize, Transaction);
+
+/// Упорядоченный по (tx_id, позиция во входном наборе) поток транзакций,
+/// полученный слиянием отсортированных временных фрагментов
+pub struct SortedTxStream {
+    runs: Vec<Lines<BufReader<File>>>,
+    heap: BinaryHeap<Reverse<(u64, usize, usize)>>,
+    heads: Vec<Option<Transaction>>,
+    _files: Vec<SpillFile>,
+}
+
+impl SortedTxStream {
+    fn read_head(&mut self, run: usize) -> Result<(), ParserError> {
+        if let Some(line) = self.runs[run].next() {
+            let (pos, tx): Positioned = serde_json::from_str(&line?)?;
+            self.heap.push(Reverse((tx.tx_id, pos, run)));
+            self.heads[run] = Some(tx);
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for SortedTxStream {
+    type Item = Result<Transaction, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, _, run)) = self.heap.pop()?;
+        let tx = self.heads[run].take()?;
+        match self.read_head(run) {
+            Ok(()) => Some(Ok(tx)),
+            Err(e) => {
+                self.heap.clear();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Функция внешней сортировки потока транзакций по tx_id
+///
+/// Поток читается фрагментами по run_size записей, каждый фрагмент сортируется в памяти и сбрасывается
+/// во временный файл (JSON Lines), затем фрагменты сливаются. Записи с одинаковым tx_id сохраняют
+/// порядок следования во входном потоке. Временные файлы удаляются при освобождении результата.
+///
+/// # Аргументы
+///
+/// * source - поток транзакций (например, [`TxReader`])
+/// * sort_options - параметры внешней сортировки
+///
+/// # Возвращаемое значение
+/// Отсортированный поток транзакций
+pub fn external_sort<I>(source: I, sort_options: &ExternalSortOptions) -> Result<SortedTxStream, ParserError>
+where
+    I: Iterator<Item = Result<Transaction, ParserError>>,
+{
+    let run_size = sort_options.run_size.max(1);
+    let mut files: Vec<SpillFile> = Vec::new();
+    let mut chunk: Vec<Positioned> = Vec::with_capacity(run_size.min(DEFAULT_RUN_SIZE));
+
+    for (pos, tx) in source.enumerate() {
+        chunk.push((pos, tx?));
+        if chunk.len() == run_size {
+            files.push(spill(&mut chunk, sort_options)?);
+        }
+    }
+    if !chunk.is_empty() {
+        files.push(spill(&mut chunk, sort_options)?);
+    }
+
+    let mut runs = Vec::with_capacity(files.len());
+    for file in &files {
+        runs.push(BufReader::new(File::open(&file.0)?).lines());
+    }
+    let mut stream = SortedTxStream { heads: vec![None; runs.len()], runs, heap: BinaryHeap::new(), _files: files };
+    for run in 0..stream.runs.len() {
+        stream.read_head(run)?;
+    }
+    Ok(stream)
+}
+
+fn spill(chunk: &mut Vec<Positioned>, sort_options: &ExternalSortOptions) -> Result<SpillFile, ParserError> {
+    chunk.sort_by_key(|(pos, tx)| (tx.tx_id, *pos));
+
+    let file = SpillFile(sort_options.temp_dir.join(format!("bis-run-{}-{}.jsonl", std::process::id(),
+        SPILL_COUNTER.fetch_add(1, Ordering::Relaxed))));
+    let mut writer = BufWriter::new(File::create(&file.0)?);
+    for record in chunk.drain(..) {
+        serde_json::to_writer(&mut writer, &record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(file)
+}
+
+/// Результат сравнения файлов с внешней сортировкой
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct FileDiff {
+    /// - report – результат сравнения, совпадает с результатом [`diff_tx_sets_with`] для тех же наборов
+    pub report: DiffReport,
+    /// - multiplicity – расхождения числа повторов записей, см. [`multiplicity_diff`]
+    pub multiplicity: Vec<MultiplicityDiff>,
+    /// - left_total – число записей первого файла
+    pub left_total: usize,
+    /// - right_total – число записей второго файла
+    pub right_total: usize,
+}
+
+/// Функция сравнения файлов транзакций с ограниченным расходом памяти
+///
+/// Оба файла читаются потоково и сортируются внешней сортировкой по tx_id, затем отсортированные потоки
+/// объединяются слиянием: в памяти одновременно находятся только записи с одним tx_id.
+/// Поскольку записи с разными tx_id никогда не сопоставляются, результат совпадает
+/// с результатом сравнения в памяти.
+///
+/// # Аргументы
+///
+/// * left_file: &str, left_format - первый файл и его формат
+/// * right_file: &str, right_format - второй файл и его формат
+/// * options: &CompareOptions - правила сравнения, TX_ID должен быть ключевым и сравниваемым полем
+/// * sort_options: &ExternalSortOptions - параметры внешней сортировки
+///
+/// # Ошибки
+/// ParserError::InvalidArgs - TX_ID не входит в ключевые поля или игнорируется,
+/// ошибки чтения и парсинга файлов.
+///
+/// # Пример
+/// ```ignore
+/// let diff = diff_tx_files("archive_a.bin", TransactionsFormatType::BIN, "archive_b.csv",
+///     TransactionsFormatType::CSV, &CompareOptions::default(), &ExternalSortOptions::default())?;
+/// println!("{} records differ", diff.report.changed.len());
+/// ```
+pub fn diff_tx_files(left_file: &str, left_format: TransactionsFormatType, right_file: &str,
+    right_format: TransactionsFormatType, options: &CompareOptions,
+    sort_options: &ExternalSortOptions) -> Result<FileDiff, ParserError> {
+
+    if !options.key_fields.contains(&TransactionField::TxId) || options.ignore_fields.contains(&TransactionField::TxId) {
+        return Err(ParserError::InvalidArgs("External comparison requires TX_ID as a compared key field".to_owned()));
+    }
+
+    let mut left = external_sort(TxReader::open(left_file, left_format)?, sort_options)?.peekable();
+    let mut right = external_sort(TxReader::open(right_file, right_format)?, sort_options)?.peekable();
+
+    let mut result = FileDiff::default();
+    loop {
+        let next_id = |stream: &mut std::iter::Peekable<SortedTxStream>| match stream.peek() {
+            Some(Ok(tx)) => Ok(Some(tx.tx_id)),
+            Some(Err(_)) => stream.next().transpose().map(|_| None),
+            None => Ok(None),
+        };
+        let tx_id = match (next_id(&mut left)?, next_id(&mut right)?) {
+            (Some(l), Some(r)) => l.min(r),
+            (Some(l), None) => l,
+            (None, Some(r)) => r,
+            (None, None) => break,
+        };
+
+        let left_group = take_group(&mut left, tx_id)?;
+        let right_group = take_group(&mut right, tx_id)?;
+        result.left_total += left_group.len();
+        result.right_total += right_group.len();
+
+        let group = diff_tx_sets_with(&left_group, &right_group, options);
+        result.report.only_left.extend(group.only_left);
+        result.report.only_right.extend(group.only_right);
+        result.report.changed.extend(group.changed);
+        result.multiplicity.extend(multiplicity_diff(&left_group, &right_group));
+    }
+    Ok(result)
+}
+
+fn take_group(stream: &mut std::iter::Peekable<SortedTxStream>, tx_id: u64) -> Result<Vec<Transaction>, ParserError> {
+    let mut group: Vec<Transaction> = Vec::new();
+    while let Some(tx) = stream.next_if(|tx| tx.as_ref().is_ok_and(|tx| tx.tx_id == tx_id)) {
+        group.push(tx?);
+    }
+    Ok(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, TransactionsParser, diff::diff_tx_sets, get_format_value, get_parser_for_format,
+        test_tx};
+
+    #[test]
+    fn test_tx_reader() -> Result<(), ParserError> {
+        for format in ["txt", "csv", "bin"] {
+            let file_name = format!("src/example/records_example.{}", format);
+            let streamed: Vec<Transaction> = TxReader::open(&file_name, get_format_value(format))?
+                .collect::<Result<_, _>>()?;
+            let loaded = get_parser_for_format(get_format_value(format)).from_read(&mut File::open(&file_name)?)?;
+            assert_eq!(streamed, loaded);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_sort() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 1).description("a").build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 1).description("b").build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 2).description("c").build()?,
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 1).description("d").build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 0).description("e").build()?,
+        ];
+        let sort_options = ExternalSortOptions { run_size: 2, ..Default::default() };
+        let sorted: Vec<(u64, String)> = external_sort(data.into_iter().map(Ok), &sort_options)?
+            .map(|tx| tx.map(|tx| (tx.tx_id, tx.description)))
+            .collect::<Result<_, _>>()?;
+        let expected: Vec<(u64, String)> = [(1, "d"), (3, "b"), (3, "e"), (5, "a"), (5, "c")]
+            .into_iter()
+            .map(|(tx_id, description)| (tx_id, description.to_owned()))
+            .collect();
+        assert_eq!(sorted, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_tx_files() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()?,
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).description("a").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 1).description("e").build()?,
+            test_tx(7, TransactionType::DEPOSIT, 0, 10, 1).description("x").build()?,
+            test_tx(7, TransactionType::DEPOSIT, 0, 10, 2).description("y").build()?,
+        ];
+        let right = vec![
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 100).description("d").build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).description("b").build()?,
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 150).description("a").build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).description("c").build()?,
+            test_tx(7, TransactionType::DEPOSIT, 0, 10, 3).description("z").build()?,
+            test_tx(7, TransactionType::DEPOSIT, 0, 10, 4).description("w").build()?,
+        ];
+
+        let temp_file = |suffix: &str| SpillFile(std::env::temp_dir().join(format!("bis-test-{}-{}-{}",
+            std::process::id(), SPILL_COUNTER.fetch_add(1, Ordering::Relaxed), suffix)));
+        let (left_file, right_file) = (temp_file("left.csv"), temp_file("right.bin"));
+        let (left_name, right_name) = (left_file.0.to_str().ok_or("path")?, right_file.0.to_str().ok_or("path")?);
+        get_parser_for_format(TransactionsFormatType::CSV).write_to(&mut File::create(left_name)?, &left)?;
+        get_parser_for_format(TransactionsFormatType::BIN).write_to(&mut File::create(right_name)?, &right)?;
+
+        let sort_options = ExternalSortOptions { run_size: 2, ..Default::default() };
+        let result = diff_tx_files(left_name, TransactionsFormatType::CSV, right_name, TransactionsFormatType::BIN,
+            &CompareOptions::default(), &sort_options)?;
+        assert_eq!(result.report, diff_tx_sets(&left, &right));
+        assert_eq!(result.multiplicity, multiplicity_diff(&left, &right));
+        assert_eq!((result.left_total, result.right_total), (7, 6));
+
+        let by_description = CompareOptions { key_fields: vec![TransactionField::Description], ..Default::default() };
+        assert!(matches!(diff_tx_files(left_name, TransactionsFormatType::CSV, right_name,
+            TransactionsFormatType::BIN, &by_description, &sort_options), Err(ParserError::InvalidArgs(_))));
+        Ok(())
+    }
+}
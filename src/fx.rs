@@ -0,0 +1,291 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    FX_RATES_HEADER, Transaction,
+    amount::{Rounding, currency_scale},
+    error::{FxError, ParserError},
+    parse_u64_value, split_csv_line,
+};
+
+/// Курс валюты в точной десятичной записи: mantissa / 10^scale
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rate {
+    /// - mantissa – значащие цифры курса
+    pub mantissa: u128,
+    /// - scale – число разрядов дробной части
+    pub scale: u32,
+}
+
+impl FromStr for Rate {
+    type Err = FxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FxError::InvalidRate(s.to_string());
+        let trimmed = s.trim().trim_matches('"');
+
+        let (int_part, frac_part) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+        if int_part.len() + frac_part.len() == 0
+            || !int_part.chars().chain(frac_part.chars()).all(|ch| ch.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let mut mantissa: u128 = 0;
+        for ch in int_part.chars().chain(frac_part.chars()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(ch.to_digit(10).unwrap_or(0) as u128))
+                .ok_or_else(invalid)?;
+        }
+        if mantissa == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { mantissa, scale: frac_part.len() as u32 })
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = format!("{:0width$}", self.mantissa, width = self.scale as usize + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - self.scale as usize);
+        if frac_part.is_empty() {
+            write!(f, "{}", int_part)
+        } else {
+            write!(f, "{}.{}", int_part, frac_part)
+        }
+    }
+}
+
+/// Запись таблицы курсов: 1 единица валюты from стоит rate единиц валюты to начиная с valid_from
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FxRate {
+    /// - from – код исходной валюты (ISO 4217)
+    pub from: String,
+    /// - to – код валюты пересчета (ISO 4217)
+    pub to: String,
+    /// - valid_from – Unix epoch timestamp начала действия курса в миллисекундах
+    pub valid_from: u64,
+    /// - rate – курс
+    pub rate: Rate,
+}
+
+/// Парсер таблицы курсов (формат CSV: FROM,TO,VALID_FROM,RATE)
+#[derive(Default)]
+pub struct RatesParser {
+
+}
+
+impl RatesParser {
+    /// Функция чтения таблицы курсов из источника
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_read<R: std::io::Read>(&self, source: &mut R) -> Result<Vec<FxRate>, ParserError> {
+        let mut result: Vec<FxRate> = Vec::new();
+
+        let mut str_records = String::new();
+        source.read_to_string(&mut str_records)?;
+
+        let str_arr: Vec<&str> = str_records
+            .split("\n")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .filter(|s| !s.starts_with("FROM"))
+            .collect();
+
+        for (numb, stx) in str_arr.iter().enumerate() {
+            let tmp_vec: Vec<&str> = split_csv_line(stx);
+
+            if tmp_vec.len() != 4 {
+                return Err(ParserError::InvalidCSVStructure(numb));
+            }
+
+            result.push(FxRate {
+                from: tmp_vec[0].to_ascii_uppercase(),
+                to: tmp_vec[1].to_ascii_uppercase(),
+                valid_from: parse_u64_value(tmp_vec[2], numb)?,
+                rate: tmp_vec[3].parse::<Rate>()?,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Функция записи таблицы курсов в источник
+    /// * target - получатель данных реализуйщий трейт Write (файл, буфер),
+    /// * data - записи таблицы курсов
+    pub fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[FxRate]) -> Result<(), ParserError> {
+        let mut result_str = String::from(FX_RATES_HEADER);
+
+        for rate in data {
+            result_str += format!("{},{},{},{}\n", rate.from, rate.to, rate.valid_from, rate.rate).as_str();
+        }
+        target.write_all(result_str.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Таблица курсов с датами начала действия
+#[derive(Debug, Default, Clone)]
+pub struct RateTable {
+    rates: HashMap<(String, String), Vec<(u64, Rate)>>,
+}
+
+impl RateTable {
+    /// Конструктор таблицы из записей курсов, порядок записей не важен
+    pub fn new(data: Vec<FxRate>) -> Self {
+        let mut rates: HashMap<(String, String), Vec<(u64, Rate)>> = HashMap::new();
+        for rate in data {
+            rates.entry((rate.from, rate.to)).or_default().push((rate.valid_from, rate.rate));
+        }
+        for history in rates.values_mut() {
+            history.sort_by_key(|(valid_from, _)| *valid_from);
+        }
+        Self { rates }
+    }
+
+    /// Возвращает курс пары валют, действующий на момент timestamp: последний курс с valid_from <= timestamp.
+    /// Для совпадающих валют возвращается курс 1. Обратные курсы не вычисляются.
+    pub fn rate(&self, from: &str, to: &str, timestamp: u64) -> Option<Rate> {
+        let (from, to) = (from.to_ascii_uppercase(), to.to_ascii_uppercase());
+        if from == to {
+            return Some(Rate { mantissa: 1, scale: 0 });
+        }
+        let history = self.rates.get(&(from, to))?;
+        let pos = history.partition_point(|(valid_from, _)| *valid_from <= timestamp);
+        pos.checked_sub(1).map(|pos| history[pos].1)
+    }
+}
+
+/// Функция пересчета сумм транзакций в валюту отчетности
+///
+/// Сумма в минорных единицах исходной валюты умножается на курс, действующий на момент транзакции,
+/// и приводится к минорным единицам валюты отчетности (см. [`currency_scale`]).
+/// Остаток округляется по правилу rounding.
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции в исходной валюте
+/// * from: &str - код исходной валюты
+/// * to: &str - код валюты отчетности
+/// * table: &RateTable - таблица курсов
+/// * rounding: Rounding - правило округления
+///
+/// # Возвращаемое значение
+/// Транзакции с пересчитанными суммами, остальные поля не изменяются
+///
+/// # Ошибки
+/// FxError - нет курса на момент транзакции, потеря точности при Rounding::Exact, переполнение.
+///
+/// # Пример
+/// ```ignore
+/// let table = RateTable::new(RatesParser::default().from_read(&mut rates_file)?);
+/// let converted = convert_transactions(&tx_vec, "USD", "RUB", &table, Rounding::HalfEven)?;
+/// ```
+pub fn convert_transactions(data: &[Transaction], from: &str, to: &str, table: &RateTable,
+    rounding: Rounding) -> Result<Vec<Transaction>, FxError> {
+
+    data.iter()
+        .map(|tx| {
+            let rate = table.rate(from, to, tx.timestamp).ok_or_else(|| FxError::MissingRate {
+                from: from.to_ascii_uppercase(),
+                to: to.to_ascii_uppercase(),
+                timestamp: tx.timestamp,
+            })?;
+            let amount = convert_amount(tx.tx_id, tx.amount, rate, currency_scale(from), currency_scale(to),
+                rounding)?;
+            Ok(Transaction { amount, ..tx.clone() })
+        })
+        .collect()
+}
+
+/// Пересчитывает сумму транзакции tx_id: amount * mantissa * 10^to_scale / 10^(rate.scale + from_scale)
+/// с округлением остатка
+fn convert_amount(tx_id: u64, amount: u64, rate: Rate, from_scale: u32, to_scale: u32,
+    rounding: Rounding) -> Result<u64, FxError> {
+
+    let overflow = || FxError::Overflow { tx_id };
+
+    let numerator = 10u128
+        .checked_pow(to_scale)
+        .and_then(|pow| pow.checked_mul(amount as u128))
+        .and_then(|val| val.checked_mul(rate.mantissa))
+        .ok_or_else(overflow)?;
+    let denominator = 10u128.checked_pow(rate.scale + from_scale).ok_or_else(overflow)?;
+
+    let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+    let round_up = if remainder == 0 {
+        false
+    } else {
+        // remainder < denominator <= 10^38, удвоение не переполняет u128
+        match rounding {
+            Rounding::Exact => return Err(FxError::Precision { tx_id }),
+            Rounding::Down => false,
+            Rounding::Up => true,
+            Rounding::HalfUp => remainder * 2 >= denominator,
+            Rounding::HalfEven => {
+                remainder * 2 > denominator || (remainder * 2 == denominator && quotient % 2 == 1)
+            }
+        }
+    };
+
+    u64::try_from(quotient + round_up as u128).map_err(|_| overflow())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    #[test]
+    fn test_rates_round_trip() -> Result<(), ParserError> {
+        let csv = FX_RATES_HEADER.to_owned() + "usd,RUB,1000,92.5\nUSD,JPY,0,\"149.125\"\nKWD,USD,0,3.25\n";
+        let parser = RatesParser::default();
+        let data = parser.from_read(&mut csv.as_bytes())?;
+        assert_eq!(data[0], FxRate { from: "USD".to_owned(), to: "RUB".to_owned(), valid_from: 1000,
+            rate: Rate { mantissa: 925, scale: 1 } });
+
+        let mut buf: Vec<u8> = Vec::new();
+        parser.write_to(&mut buf, &data)?;
+        assert_eq!(String::from_utf8_lossy(&buf), FX_RATES_HEADER.to_owned() +
+            "USD,RUB,1000,92.5\nUSD,JPY,0,149.125\nKWD,USD,0,3.25\n");
+
+        assert!(matches!("0.00".parse::<Rate>(), Err(FxError::InvalidRate(_))));
+        assert!(matches!("1,5".parse::<Rate>(), Err(FxError::InvalidRate(_))));
+        assert_eq!("0.05".parse::<Rate>().map(|rate| rate.to_string()), Ok("0.05".to_owned()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_transactions() -> Result<(), Box<dyn std::error::Error>> {
+        let table = RateTable::new(vec![
+            FxRate { from: "USD".to_owned(), to: "RUB".to_owned(), valid_from: 1000,
+                rate: Rate { mantissa: 925, scale: 1 } },
+            FxRate { from: "USD".to_owned(), to: "RUB".to_owned(), valid_from: 0,
+                rate: Rate { mantissa: 90, scale: 0 } },
+            FxRate { from: "USD".to_owned(), to: "JPY".to_owned(), valid_from: 0,
+                rate: Rate { mantissa: 149_125, scale: 3 } },
+        ]);
+        assert_eq!(table.rate("usd", "rub", 999), Some(Rate { mantissa: 90, scale: 0 }));
+        assert_eq!(table.rate("RUB", "USD", 999), None);
+
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 150).timestamp(500).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 150).timestamp(1500).build()?,
+        ];
+        let rub: Vec<u64> = convert_transactions(&data, "USD", "RUB", &table, Rounding::Exact)
+            .map(|txs| txs.iter().map(|tx| tx.amount).collect())
+            .unwrap_or_default();
+        assert_eq!(rub, vec![13500, 13875]);
+
+        // 1.50 USD * 149.125 = 223.6875 JPY
+        let jpy = |rounding| {
+            convert_transactions(&data[..1], "USD", "JPY", &table, rounding).map(|txs| txs[0].amount)
+        };
+        assert_eq!(jpy(Rounding::HalfEven), Ok(224));
+        assert_eq!(jpy(Rounding::Down), Ok(223));
+        assert_eq!(jpy(Rounding::Exact), Err(FxError::Precision { tx_id: 1 }));
+
+        assert_eq!(convert_transactions(&data, "USD", "RUB", &RateTable::default(), Rounding::Exact),
+            Err(FxError::MissingRate { from: "USD".to_owned(), to: "RUB".to_owned(), timestamp: 500 }));
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    JOURNAL_HEADER, Transaction, TransactionStatus, TransactionType, error::{JournalError, ParserError}};
+
+/// Ссылка на счет в плане счетов
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum AccountRef {
+    /// - Ledger – фиксированный счет (касса, клиринг, счет комиссий)
+    Ledger(String),
+    /// - FromUser – счет отправителя: `<user_prefix><from_user_id>`
+    FromUser,
+    /// - ToUser – счет получателя: `<user_prefix><to_user_id>`
+    ToUser,
+}
+
+/// Правило проводки для типа транзакции: счет дебета и счет кредита
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct PostingRule {
+    /// - debit – счет дебета
+    pub debit: AccountRef,
+    /// - credit – счет кредита
+    pub credit: AccountRef,
+}
+
+/// План счетов: правила проводок для типов транзакций
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ChartOfAccounts {
+    /// - user_prefix – префикс счетов пользователей
+    pub user_prefix: String,
+    /// - rules – правила проводок по типам транзакций
+    pub rules: HashMap<TransactionType, PostingRule>,
+}
+
+impl Default for ChartOfAccounts {
+    /// План счетов по умолчанию:
+    /// * DEPOSIT - Дт касса/клиринг, Кт счет получателя,
+    /// * WITHDRAWAL - Дт счет отправителя, Кт касса/клиринг,
+    /// * TRANSFER - Дт счет отправителя, Кт счет получателя.
+    fn default() -> Self {
+        let cash = AccountRef::Ledger("1000-CASH".to_owned());
+        Self {
+            user_prefix: "2000-USER-".to_owned(),
+            rules: HashMap::from([
+                (TransactionType::DEPOSIT, PostingRule { debit: cash.clone(), credit: AccountRef::ToUser }),
+                (TransactionType::WITHDRAWAL, PostingRule { debit: AccountRef::FromUser, credit: cash }),
+                (TransactionType::TRANSFER, PostingRule { debit: AccountRef::FromUser, credit: AccountRef::ToUser }),
+            ]),
+        }
+    }
+}
+
+impl ChartOfAccounts {
+    /// Функция чтения плана счетов в формате JSON
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    pub fn from_read<R: std::io::Read>(source: &mut R) -> Result<Self, ParserError> {
+        Ok(serde_json::from_reader(source)?)
+    }
+
+    fn account_name(&self, account: &AccountRef, tx: &Transaction) -> String {
+        match account {
+            AccountRef::Ledger(name) => name.clone(),
+            AccountRef::FromUser => self.user_prefix.clone() + tx.from_user_id.to_string().as_str(),
+            AccountRef::ToUser => self.user_prefix.clone() + tx.to_user_id.to_string().as_str(),
+        }
+    }
+}
+
+/// Проводка журнала
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct JournalPosting {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - timestamp – время транзакции
+    pub timestamp: u64,
+    /// - account – счет
+    pub account: String,
+    /// - debit – сумма по дебету
+    pub debit: u64,
+    /// - credit – сумма по кредиту
+    pub credit: u64,
+    /// - description – описание транзакции
+    pub description: String,
+}
+
+/// Функция формирования проводок двойной записи по транзакциям
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции, учитываются только транзакции в статусе SUCCESS
+/// * chart: &ChartOfAccounts - план счетов
+///
+/// # Возвращаемое значение
+/// Проводки (по две на транзакцию: дебет и кредит) в порядке следования транзакций
+///
+/// # Ошибки
+/// JournalError::MissingRule - для типа транзакции не задано правило проводки.
+pub fn journal_postings(data: &[Transaction], chart: &ChartOfAccounts) -> Result<Vec<JournalPosting>, JournalError> {
+    let mut result: Vec<JournalPosting> = Vec::with_capacity(data.len() * 2);
+
+    for tx in data.iter().filter(|tx| tx.status == TransactionStatus::SUCCESS) {
+        let rule = chart
+            .rules
+            .get(&tx.tx_type)
+            .ok_or_else(|| JournalError::MissingRule(tx.tx_type.clone()))?;
+
+        let posting = |account: &AccountRef, debit: u64, credit: u64| JournalPosting {
+            tx_id: tx.tx_id,
+            timestamp: tx.timestamp,
+            account: chart.account_name(account, tx),
+            debit,
+            credit,
+            description: tx.description.clone(),
+        };
+        result.push(posting(&rule.debit, tx.amount, 0));
+        result.push(posting(&rule.credit, 0, tx.amount));
+    }
+    Ok(result)
+}
+
+/// Функция проверки сбалансированности журнала
+///
+/// # Ошибки
+/// JournalError::Unbalanced - сумма дебета проводок транзакции не равна сумме кредита.
+pub fn check_balanced(postings: &[JournalPosting]) -> Result<(), JournalError> {
+    let mut totals: BTreeMap<u64, (u128, u128)> = BTreeMap::new();
+
+    for posting in postings {
+        let total = totals.entry(posting.tx_id).or_default();
+        total.0 += posting.debit as u128;
+        total.1 += posting.credit as u128;
+    }
+
+    match totals.into_iter().find(|(_, (debit, credit))| debit != credit) {
+        Some((tx_id, (debit, credit))) => Err(JournalError::Unbalanced { tx_id, debit, credit }),
+        None => Ok(()),
+    }
+}
+
+/// Функция записи проводок в формате CSV
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер)
+/// * postings - проводки журнала
+pub fn write_journal_csv<W: std::io::Write>(target: &mut W, postings: &[JournalPosting]) -> Result<(), ParserError> {
+    let mut result_str = String::from(JOURNAL_HEADER);
+
+    for posting in postings {
+        result_str += format!("{},{},{},{},{},\"{}\"\n", posting.tx_id, posting.timestamp, posting.account,
+            posting.debit, posting.credit, posting.description).as_str();
+    }
+    target.write_all(result_str.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tx;
+
+    #[test]
+    fn test_journal_postings() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 10, 20, 100).description("Journal").build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 100).description("Journal").build()?,
+            test_tx(3, TransactionType::WITHDRAWAL, 10, 20, 100).status(TransactionStatus::PENDING).build()?,
+        ];
+        let postings = journal_postings(&data, &ChartOfAccounts::default())?;
+        check_balanced(&postings)?;
+
+        let mut buf: Vec<u8> = Vec::new();
+        write_journal_csv(&mut buf, &postings)?;
+        assert_eq!(String::from_utf8(buf)?, JOURNAL_HEADER.to_owned() +
+            "1,0,1000-CASH,100,0,\"Journal\"\n" +
+            "1,0,2000-USER-20,0,100,\"Journal\"\n" +
+            "2,0,2000-USER-10,100,0,\"Journal\"\n" +
+            "2,0,2000-USER-20,0,100,\"Journal\"\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_configured_chart() -> Result<(), Box<dyn std::error::Error>> {
+        let json = r#"{"user_prefix": "U", "rules": {
+            "DEPOSIT": {"debit": {"Ledger": "CLEARING"}, "credit": "ToUser"}
+        }}"#;
+        let chart = ChartOfAccounts::from_read(&mut json.as_bytes())?;
+
+        let postings = journal_postings(&[test_tx(1, TransactionType::DEPOSIT, 10, 20, 100).build()?], &chart)?;
+        assert_eq!(postings[0].account, "CLEARING");
+        assert_eq!(postings[1].account, "U20");
+
+        let res = journal_postings(&[test_tx(2, TransactionType::TRANSFER, 10, 20, 100).build()?], &chart);
+        assert_eq!(res, Err(JournalError::MissingRule(TransactionType::TRANSFER)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_unbalanced() -> Result<(), Box<dyn std::error::Error>> {
+        let mut postings = journal_postings(&[test_tx(1, TransactionType::DEPOSIT, 10, 20, 100).build()?],
+            &ChartOfAccounts::default())?;
+        postings[1].credit = 90;
+        assert_eq!(check_balanced(&postings), Err(JournalError::Unbalanced { tx_id: 1, debit: 100, credit: 90 }));
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+
+use crate::{BALANCES_HEADER, Transaction, TransactionStatus, TransactionType, error::{LedgerError, ParserError}};
+
+/// Сведения о превышении остатка (отрицательном балансе) после применения транзакции
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Overdraft {
+    /// - tx_id – идентификатор транзакции, приведшей к отрицательному балансу
+    pub tx_id: u64,
+    /// - user_id – идентификатор пользователя
+    pub user_id: u64,
+    /// - balance – баланс пользователя после применения транзакции
+    pub balance: i64,
+}
+
+/// Результат применения набора транзакций к балансам пользователей
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct LedgerReport {
+    /// - balances – итоговые балансы пользователей
+    pub balances: BTreeMap<u64, i64>,
+    /// - overdrafts – транзакции, после которых баланс пользователя стал отрицательным
+    pub overdrafts: Vec<Overdraft>,
+    /// - overflows – ошибки переполнения, транзакции не применены
+    pub overflows: Vec<LedgerError>,
+}
+
+/// Книга балансов пользователей
+#[derive(Debug, Default, Clone)]
+pub struct Ledger {
+    balances: BTreeMap<u64, i64>,
+}
+
+impl Ledger {
+    /// Конструктор книги с нулевыми балансами
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Конструктор книги с начальными балансами
+    pub fn with_balances(balances: BTreeMap<u64, i64>) -> Self {
+        Self { balances }
+    }
+
+    /// Возвращает баланс пользователя (0 для неизвестного пользователя)
+    pub fn balance(&self, user_id: u64) -> i64 {
+        self.balances.get(&user_id).copied().unwrap_or(0)
+    }
+
+    /// Возвращает текущие балансы пользователей
+    pub fn balances(&self) -> &BTreeMap<u64, i64> {
+        &self.balances
+    }
+
+    /// Функция применения транзакции к балансам
+    ///
+    /// Учитываются только транзакции в статусе SUCCESS:
+    /// * DEPOSIT - зачисление на счет to_user_id,
+    /// * WITHDRAWAL - списание со счета from_user_id,
+    /// * TRANSFER - перевод со счета from_user_id на счет to_user_id.
+    ///
+    /// # Возвращаемое значение
+    /// Сведения о превышении остатка, если баланс списания стал отрицательным, иначе - None.
+    ///
+    /// # Ошибки
+    /// LedgerError::Overflow - переполнение баланса, транзакция не применяется.
+    pub fn apply(&mut self, tx: &Transaction) -> Result<Option<Overdraft>, LedgerError> {
+        let (debit, credit) = match movement(tx) {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
+        let overflow = |user_id| LedgerError::Overflow { tx_id: tx.tx_id, user_id };
+        let amount = i64::try_from(tx.amount);
+
+        let mut updates: Vec<(u64, i64)> = Vec::with_capacity(2);
+        if let Some(user_id) = debit {
+            let balance = amount
+                .ok()
+                .and_then(|amount| self.balance(user_id).checked_sub(amount))
+                .ok_or_else(|| overflow(user_id))?;
+            updates.push((user_id, balance));
+        }
+        if let Some(user_id) = credit {
+            let current = updates
+                .iter()
+                .find(|(id, _)| *id == user_id)
+                .map(|(_, balance)| *balance)
+                .unwrap_or_else(|| self.balance(user_id));
+            let balance = amount
+                .ok()
+                .and_then(|amount| current.checked_add(amount))
+                .ok_or_else(|| overflow(user_id))?;
+            updates.push((user_id, balance));
+        }
+
+        for (user_id, balance) in updates {
+            self.balances.insert(user_id, balance);
+        }
+
+        Ok(debit
+            .map(|user_id| (user_id, self.balance(user_id)))
+            .filter(|(_, balance)| *balance < 0)
+            .map(|(user_id, balance)| Overdraft { tx_id: tx.tx_id, user_id, balance }))
+    }
+
+    /// Функция применения набора транзакций к балансам
+    ///
+    /// # Возвращаемое значение
+    /// LedgerReport с итоговыми балансами, превышениями остатка и ошибками переполнения
+    pub fn apply_all(mut self, data: &[Transaction]) -> LedgerReport {
+        let mut overdrafts: Vec<Overdraft> = Vec::new();
+        let mut overflows: Vec<LedgerError> = Vec::new();
+
+        for tx in data {
+            match self.apply(tx) {
+                Ok(Some(overdraft)) => overdrafts.push(overdraft),
+                Ok(None) => (),
+                Err(e) => overflows.push(e),
+            }
+        }
+
+        LedgerReport { balances: self.balances, overdrafts, overflows }
+    }
+}
+
+/// Возвращает пару (счет списания, счет зачисления) для учитываемой транзакции,
+/// None - транзакция не влияет на балансы
+fn movement(tx: &Transaction) -> Option<(Option<u64>, Option<u64>)> {
+    if tx.status != TransactionStatus::SUCCESS {
+        return None;
+    }
+    direction(tx)
+}
+
+/// Возвращает пару (счет списания, счет зачисления) в соответствии с типом транзакции без учета статуса
+fn direction(tx: &Transaction) -> Option<(Option<u64>, Option<u64>)> {
+    match tx.tx_type {
+        TransactionType::DEPOSIT => Some((None, Some(tx.to_user_id))),
+        TransactionType::WITHDRAWAL => Some((Some(tx.from_user_id), None)),
+        TransactionType::TRANSFER => Some((Some(tx.from_user_id), Some(tx.to_user_id))),
+        TransactionType::EMPTY => None,
+    }
+}
+
+fn signed(moves: Option<(Option<u64>, Option<u64>)>, amount: u64, user_id: u64) -> i128 {
+    match moves {
+        Some((debit, credit)) => {
+            let mut delta: i128 = 0;
+            if debit == Some(user_id) {
+                delta -= amount as i128;
+            }
+            if credit == Some(user_id) {
+                delta += amount as i128;
+            }
+            delta
+        }
+        None => 0,
+    }
+}
+
+/// Функция вычисления изменения баланса пользователя в результате транзакции
+///
+/// # Возвращаемое значение
+/// Знаковая сумма с точки зрения пользователя user_id: положительная - зачисление,
+/// отрицательная - списание, 0 - транзакция не затрагивает баланс пользователя
+/// (в том числе транзакция не в статусе SUCCESS).
+pub fn user_delta(tx: &Transaction, user_id: u64) -> i128 {
+    signed(movement(tx), tx.amount, user_id)
+}
+
+/// Функция вычисления знаковой суммы транзакции с точки зрения пользователя без учета статуса
+///
+/// # Возвращаемое значение
+/// Положительная сумма - зачисление пользователю, отрицательная - списание,
+/// 0 - транзакция не затрагивает пользователя.
+pub fn signed_amount(tx: &Transaction, user_id: u64) -> i128 {
+    signed(direction(tx), tx.amount, user_id)
+}
+
+/// Функция записи балансов пользователей в формате CSV
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер)
+/// * balances - балансы пользователей
+pub fn write_balances_csv<W: std::io::Write>(target: &mut W, balances: &BTreeMap<u64, i64>) -> Result<(), ParserError> {
+    let mut result_str = String::from(BALANCES_HEADER);
+
+    for (user_id, balance) in balances {
+        result_str += format!("{},{}\n", user_id, balance).as_str();
+    }
+    target.write_all(result_str.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tx;
+
+    #[test]
+    fn test_apply_all() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 500).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 200).build()?,
+            test_tx(3, TransactionType::WITHDRAWAL, 20, 0, 50).build()?,
+            test_tx(4, TransactionType::WITHDRAWAL, 10, 0, 1000).status(TransactionStatus::FAILURE).build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 20, 1000).status(TransactionStatus::PENDING).build()?,
+        ];
+        let report = Ledger::new().apply_all(&data);
+        assert_eq!(report.balances, BTreeMap::from([(10, 300), (20, 150)]));
+        assert!(report.overdrafts.is_empty());
+        assert!(report.overflows.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_overdraft_and_overflow() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::WITHDRAWAL, 10, 0, 100).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 20, i64::MAX as u64).build()?,
+            test_tx(3, TransactionType::TRANSFER, 10, 20, 1).build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 30, u64::MAX).build()?,
+        ];
+        let report = Ledger::new().apply_all(&data);
+        assert_eq!(report.overdrafts, vec![Overdraft { tx_id: 1, user_id: 10, balance: -100 }]);
+        assert_eq!(report.overflows, vec![
+            LedgerError::Overflow { tx_id: 3, user_id: 20 },
+            LedgerError::Overflow { tx_id: 4, user_id: 30 },
+        ]);
+        assert_eq!(report.balances, BTreeMap::from([(10, -100), (20, i64::MAX)]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_balances_csv() -> Result<(), ParserError> {
+        let mut buf: Vec<u8> = Vec::new();
+        write_balances_csv(&mut buf, &BTreeMap::from([(10, 300), (20, -5)]))?;
+        assert_eq!(String::from_utf8_lossy(&buf), "USER_ID,BALANCE\n10,300\n20,-5\n");
+        Ok(())
+    }
+}
@@ -1,14 +1,24 @@
 //! Библиотека парсинга и сравнения данных о финансовых транзакция.
 
 #![warn(missing_docs)] 
-use std::{collections::HashSet, fs::File, path::Path};
+use std::{fs::File, str::FromStr};
 use serde::{Serialize, Deserialize};
 use strum_macros::Display;
 
-use crate::error::ParserError;
+use crate::{amount::AmountFormat, error::{BuildError, ParserError}};
 
 /// Строка заголовка для формата CSV
 pub const CVS_HEADER: &str = "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n";
+/// Строка заголовка для файла балансов пользователей в формате CSV
+pub const BALANCES_HEADER: &str = "USER_ID,BALANCE\n";
+/// Строка заголовка для файла снимка балансов пользователей
+pub const SNAPSHOT_HEADER: &str = "USER_ID,BALANCE,AS_OF\n";
+/// Строка заголовка для журнала проводок в формате CSV
+pub const JOURNAL_HEADER: &str = "TX_ID,TIMESTAMP,ACCOUNT,DEBIT,CREDIT,DESCRIPTION\n";
+/// Строка заголовка для реестра счетов в формате CSV
+pub const ACCOUNTS_HEADER: &str = "USER_ID,NAME,ACCOUNT_TYPE,OPENED_AT,CLOSED_AT,STATUS\n";
+/// Строка заголовка для таблицы курсов валют в формате CSV
+pub const FX_RATES_HEADER: &str = "FROM,TO,VALID_FROM,RATE\n";
 /// Строковое значение маркера начала записи о транзакции для бинарного формата
 pub const MAGIC: &str = "YPBN";
 /// Базовая длина записи в байтах для формата bin
@@ -22,9 +32,53 @@ pub mod csv_format;
 pub mod bin_format;
 /// Модуль декларации ошибок
 pub mod error;
+/// Модуль чтения и записи денежных сумм в десятичной записи
+pub mod amount;
+/// Модуль расчета балансов пользователей
+pub mod ledger;
+/// Модуль чтения и записи снимков балансов и сверки балансов
+pub mod snapshot;
+/// Модуль объединения жизненного цикла транзакций из потоков обновлений статуса
+pub mod lifecycle;
+/// Модуль формирования журнала проводок двойной записи
+pub mod journal;
+/// Модуль поиска дубликатов и конфликтов идентификаторов транзакций
+pub mod duplicates;
+/// Модуль реестра счетов и проверки ссылочной целостности транзакций
+pub mod accounts;
+/// Модуль начисления процентов и комиссий
+pub mod charges;
+/// Модуль формирования выписок по счетам пользователей
+pub mod statement;
+/// Модуль расчета агрегированной статистики по транзакциям
+pub mod stats;
+/// Модуль выявления подозрительной активности по правилам
+pub mod risk;
+/// Модуль неттинга переводов и формирования расчетных транзакций
+pub mod netting;
+/// Модуль пересчета сумм по таблице курсов валют
+pub mod fx;
+/// Модуль сравнения наборов транзакций с детализацией различий
+pub mod diff;
+/// Модуль упорядоченного сравнения наборов транзакций
+pub mod ordered;
+/// Модуль сравнения файлов транзакций с внешней сортировкой
+pub mod external;
+/// Модуль трехстороннего сравнения наборов транзакций с общим базовым набором
+pub mod threeway;
+/// Модуль нечеткого сопоставления транзакций с разными tx_id
+pub mod matching;
+/// Модуль формирования и применения файлов изменений наборов транзакций
+pub mod delta;
+/// Модуль сверки наборов транзакций по итогам групп
+pub mod aggregate;
+/// Модуль разбора аргументов командной строки
+pub mod cli;
+/// Модуль календарных функций для Unix epoch timestamp в миллисекундах (UTC)
+mod calendar;
 
 /// Типы транзакций
-#[derive(Display, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, PartialOrd)]
+#[derive(Display, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
 pub enum TransactionType {
     /// - DEPOSIT – депозит
     DEPOSIT = 0, 
@@ -32,12 +86,40 @@ pub enum TransactionType {
     TRANSFER = 1, 
     /// - DEPOSIT – депозит
     WITHDRAWAL = 2,
-    /// - EMPTY - не определен
+    /// - EMPTY - не определен (допускается только при чтении в режиме обратной совместимости)
     EMPTY = 3,
 }
 
+impl FromStr for TransactionType {
+    type Err = BuildError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DEPOSIT" => Ok(Self::DEPOSIT),
+            "TRANSFER" => Ok(Self::TRANSFER),
+            "WITHDRAWAL" => Ok(Self::WITHDRAWAL),
+            "EMPTY" => Ok(Self::EMPTY),
+            _ => Err(BuildError::UnknownType(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<u8> for TransactionType {
+    type Error = BuildError;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            0 => Ok(Self::DEPOSIT),
+            1 => Ok(Self::TRANSFER),
+            2 => Ok(Self::WITHDRAWAL),
+            3 => Ok(Self::EMPTY),
+            _ => Err(BuildError::UnknownType(code.to_string())),
+        }
+    }
+}
+
 /// Статус транзакции
-#[derive(Display, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, PartialOrd)]
+#[derive(Display, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
 pub enum TransactionStatus {
     /// - SUCCESS – успешное завершение
     SUCCESS = 0,
@@ -45,10 +127,38 @@ pub enum TransactionStatus {
     FAILURE = 1,
     /// - PENDING – исполнение в процессе
     PENDING = 2,
-    /// - EMPTY - не определен
+    /// - EMPTY - не определен (допускается только при чтении в режиме обратной совместимости)
     EMPTY = 3,
 }
 
+impl FromStr for TransactionStatus {
+    type Err = BuildError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SUCCESS" => Ok(Self::SUCCESS),
+            "FAILURE" => Ok(Self::FAILURE),
+            "PENDING" => Ok(Self::PENDING),
+            "EMPTY" => Ok(Self::EMPTY),
+            _ => Err(BuildError::UnknownStatus(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<u8> for TransactionStatus {
+    type Error = BuildError;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        match code {
+            0 => Ok(Self::SUCCESS),
+            1 => Ok(Self::FAILURE),
+            2 => Ok(Self::PENDING),
+            3 => Ok(Self::EMPTY),
+            _ => Err(BuildError::UnknownStatus(code.to_string())),
+        }
+    }
+}
+
 /// Типы форматов финансовых данных
 #[derive(Display, PartialEq, Debug, Eq)]
 pub enum TransactionsFormatType {
@@ -72,37 +182,6 @@ pub enum FormatParsers {
     Bin,
 }
 
-/// Структура данных о параметрах запуска утилит
-#[derive(Debug)]
-pub struct CliParams {
-    /// - first_file_name – файл содержащий финансовые транзакции
-    pub first_file_name: String,
-    /// - first_file_format – формат файла <first_file_name> - csv, txt, bin.
-    pub first_file_format: TransactionsFormatType,
-    /// - second_file_name – файл содержащий финансовые транзакции
-    pub second_file_name: String,
-    /// - second_file_format – формат файла <second_file_name> - csv, txt, bin.
-    pub second_file_format: TransactionsFormatType,
-}
-
-impl CliParams {
-    /// Конструктор
-    pub fn new() -> Self {
-        Self {
-            first_file_name: String::new(),
-            first_file_format: TransactionsFormatType::UNKNOWN,
-            second_file_name: String::new(),
-            second_file_format: TransactionsFormatType::UNKNOWN,
-        }
-    }
-}
-
-impl Default for CliParams {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// Реализация TransactionsParser для фабрики парсеров
 impl TransactionsParser for FormatParsers {
     fn get_using_format_type(&self) -> TransactionsFormatType {
@@ -121,7 +200,7 @@ impl TransactionsParser for FormatParsers {
        }
     }
 
-    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &Vec<Transaction>) -> Result<(), ParserError> {
+    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[Transaction]) -> Result<(), ParserError> {
         match self {
             Self::Txt => txt_format::TxtParser::default().write_to(target, data),
             Self::Csv => csv_format::CsvParser::default().write_to(target, data),
@@ -131,7 +210,7 @@ impl TransactionsParser for FormatParsers {
 }
 
 /// Структура данных о финансовой транзакции
-#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Clone)]
 pub struct Transaction {
 /// - tx_id – идентификатор транзакции.
     pub tx_id: u64,
@@ -152,7 +231,8 @@ pub struct Transaction {
 }
 
 impl Transaction {
-    /// Конструктор
+    /// Конструктор пустой транзакции с неопределенными типом и статусом.
+    /// Для создания корректной транзакции используйте [`Transaction::builder`].
     pub fn new() -> Self {
         Self {
             tx_id: 0,
@@ -165,6 +245,21 @@ impl Transaction {
             description: String::new(),  
         }
     }
+
+    /// Возвращает построитель транзакции
+    pub fn builder() -> TransactionBuilder {
+        TransactionBuilder::new()
+    }
+
+    /// Возвращает идентификаторы пользователей, счета которых затрагивает транзакция:
+    /// to_user_id для DEPOSIT, from_user_id для WITHDRAWAL, оба для TRANSFER и неопределенного типа
+    pub fn participants(&self) -> Vec<u64> {
+        match self.tx_type {
+            TransactionType::DEPOSIT => vec![self.to_user_id],
+            TransactionType::WITHDRAWAL => vec![self.from_user_id],
+            TransactionType::TRANSFER | TransactionType::EMPTY => vec![self.from_user_id, self.to_user_id],
+        }
+    }
 }
 
 impl Default for Transaction {
@@ -173,6 +268,218 @@ impl Default for Transaction {
     }
 }
 
+/// Построитель транзакции.
+/// 
+/// Обязательные поля: tx_id, tx_type, from_user_id, to_user_id, amount, timestamp, status.
+/// Поле description необязательно (по умолчанию пустая строка).
+/// Значения `EMPTY` типа и статуса отклоняются, если не включен режим обратной совместимости.
+/// 
+/// # Пример
+/// ```ignore 
+/// let tx = Transaction::builder()
+///     .tx_id(1)
+///     .tx_type(TransactionType::DEPOSIT)
+///     .from_user_id(0)
+///     .to_user_id(42)
+///     .amount(100)
+///     .timestamp(1633036860000)
+///     .status(TransactionStatus::SUCCESS)
+///     .build()?;
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TransactionBuilder {
+    tx_id: Option<u64>,
+    tx_type: Option<TransactionType>,
+    from_user_id: Option<u64>,
+    to_user_id: Option<u64>,
+    amount: Option<u64>,
+    timestamp: Option<u64>,
+    status: Option<TransactionStatus>,
+    description: Option<String>,
+    allow_empty: bool,
+}
+
+impl TransactionBuilder {
+    /// Конструктор
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Устанавливает идентификатор транзакции
+    pub fn tx_id(mut self, val: u64) -> Self {
+        self.tx_id = Some(val);
+        self
+    }
+
+    /// Устанавливает тип транзакции
+    pub fn tx_type(mut self, val: TransactionType) -> Self {
+        self.tx_type = Some(val);
+        self
+    }
+
+    /// Устанавливает идентификатор отправителя
+    pub fn from_user_id(mut self, val: u64) -> Self {
+        self.from_user_id = Some(val);
+        self
+    }
+
+    /// Устанавливает идентификатор получателя
+    pub fn to_user_id(mut self, val: u64) -> Self {
+        self.to_user_id = Some(val);
+        self
+    }
+
+    /// Устанавливает сумму
+    pub fn amount(mut self, val: u64) -> Self {
+        self.amount = Some(val);
+        self
+    }
+
+    /// Устанавливает время транзакции (Unix epoch в миллисекундах)
+    pub fn timestamp(mut self, val: u64) -> Self {
+        self.timestamp = Some(val);
+        self
+    }
+
+    /// Устанавливает статус транзакции
+    pub fn status(mut self, val: TransactionStatus) -> Self {
+        self.status = Some(val);
+        self
+    }
+
+    /// Устанавливает описание транзакции
+    pub fn description(mut self, val: impl Into<String>) -> Self {
+        self.description = Some(val.into());
+        self
+    }
+
+    /// Разрешает значения `EMPTY` типа и статуса (режим обратной совместимости)
+    pub fn allow_empty(mut self, val: bool) -> Self {
+        self.allow_empty = val;
+        self
+    }
+
+    /// Создает транзакцию
+    /// 
+    /// # Ошибки
+    /// BuildError - не задано обязательное поле или тип/статус не определен.
+    pub fn build(self) -> Result<Transaction, BuildError> {
+        let tx_type = self.tx_type.ok_or(BuildError::MissingField("TX_TYPE"))?;
+        let status = self.status.ok_or(BuildError::MissingField("STATUS"))?;
+
+        if !self.allow_empty {
+            if tx_type == TransactionType::EMPTY {
+                return Err(BuildError::UndefinedType);
+            }
+            if status == TransactionStatus::EMPTY {
+                return Err(BuildError::UndefinedStatus);
+            }
+        }
+
+        Ok(Transaction {
+            tx_id: self.tx_id.ok_or(BuildError::MissingField("TX_ID"))?,
+            tx_type,
+            from_user_id: self.from_user_id.ok_or(BuildError::MissingField("FROM_USER_ID"))?,
+            to_user_id: self.to_user_id.ok_or(BuildError::MissingField("TO_USER_ID"))?,
+            amount: self.amount.ok_or(BuildError::MissingField("AMOUNT"))?,
+            timestamp: self.timestamp.ok_or(BuildError::MissingField("TIMESTAMP"))?,
+            status,
+            description: self.description.unwrap_or_default(),
+        })
+    }
+}
+
+/// Поля транзакции
+#[derive(Display, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionField {
+    /// - TX_ID – идентификатор транзакции
+    #[strum(serialize = "TX_ID")]
+    TxId,
+    /// - TX_TYPE – тип транзакции
+    #[strum(serialize = "TX_TYPE")]
+    TxType,
+    /// - FROM_USER_ID – идентификатор отправителя
+    #[strum(serialize = "FROM_USER_ID")]
+    FromUserId,
+    /// - TO_USER_ID – идентификатор получателя
+    #[strum(serialize = "TO_USER_ID")]
+    ToUserId,
+    /// - AMOUNT – сумма
+    #[strum(serialize = "AMOUNT")]
+    Amount,
+    /// - TIMESTAMP – время транзакции
+    #[strum(serialize = "TIMESTAMP")]
+    Timestamp,
+    /// - STATUS – статус транзакции
+    #[strum(serialize = "STATUS")]
+    Status,
+    /// - DESCRIPTION – описание
+    #[strum(serialize = "DESCRIPTION")]
+    Description,
+}
+
+impl TransactionField {
+    /// Все поля транзакции в порядке следования в формате CSV
+    pub const ALL: [TransactionField; 8] = [
+        Self::TxId, Self::TxType, Self::FromUserId, Self::ToUserId,
+        Self::Amount, Self::Timestamp, Self::Status, Self::Description,
+    ];
+
+    /// Возвращает строковое значение поля транзакции
+    pub fn value_of(&self, tx: &Transaction) -> String {
+        match self {
+            Self::TxId => tx.tx_id.to_string(),
+            Self::TxType => tx.tx_type.to_string(),
+            Self::FromUserId => tx.from_user_id.to_string(),
+            Self::ToUserId => tx.to_user_id.to_string(),
+            Self::Amount => tx.amount.to_string(),
+            Self::Timestamp => tx.timestamp.to_string(),
+            Self::Status => tx.status.to_string(),
+            Self::Description => tx.description.clone(),
+        }
+    }
+}
+
+impl FromStr for TransactionField {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParserError::UnknownField(s.to_string()))
+    }
+}
+
+/// Изменение значения поля транзакции
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FieldChange {
+    /// - field – поле транзакции
+    pub field: TransactionField,
+    /// - before – значение поля в первой транзакции
+    pub before: String,
+    /// - after – значение поля во второй транзакции
+    pub after: String,
+}
+
+/// Функция получения списка различающихся полей двух транзакций
+/// 
+/// # Аргументы
+/// 
+/// * tx_left: &Transaction - первая транзакция
+/// * tx_right: &Transaction - вторая транзакция
+/// 
+/// # Возвращаемое значение
+/// Список изменений полей в порядке следования полей в формате CSV, пустой - если транзакции совпадают
+pub fn diff_fields(tx_left: &Transaction, tx_right: &Transaction) -> Vec<FieldChange> {
+    TransactionField::ALL
+        .into_iter()
+        .map(|field| FieldChange { field, before: field.value_of(tx_left), after: field.value_of(tx_right) })
+        .filter(|change| change.before != change.after)
+        .collect()
+}
+
 /// Трейт функциональности парсера данных из формата 
 pub trait TransactionsParser {
 
@@ -181,40 +488,49 @@ pub trait TransactionsParser {
 
     /// Функция чтения из источника представленного в конкретном формате финансовых данных
     /// * source - источник данных содержащий транзакции и реализуйщий трейт Read (файл, буфер) в формате, обрабатываемом парсером
+    #[allow(clippy::wrong_self_convention)]
     fn from_read<Reader: std::io::Read>(&self, source: &mut Reader) -> Result<Vec<Transaction>, ParserError>;
 
     /// Функция записи набора транзакций в источник в конкретном формате финансовых данных
     /// * target - получатель данных реализуйщий трейт Write (файл, буфер), 
     /// * data - данные о финансовых транзакциях
-    fn write_to<Writer: std::io::Write>(&self, target: &mut Writer, data: &Vec<Transaction>) -> Result<(), ParserError>;
+    fn write_to<Writer: std::io::Write>(&self, target: &mut Writer, data: &[Transaction]) -> Result<(), ParserError>;
 }
 
-fn txt_to_json_str(tx: &str) -> Result<String, ParserError> {
-    let tx_json = &tx.replace(": ", ":");
-
-    let json_vec: Vec<&str> = tx_json.split(":").collect();
-
-    if json_vec.len() != 2 {
-        return Result::Err(ParserError::InvalidTxtStrStructure(tx.to_string()));
-    }
-
-    let type_val; 
-    let status_val; 
+fn parse_u64_value(val: &str, tx_numb: usize) -> Result<u64, ParserError> {
+    val.trim().parse::<u64>().map_err(|_| ParserError::InvalidValue { tx_numb })
+}
 
-    let key_str = "\"".to_owned() + json_vec[0].to_ascii_lowercase().as_str() + "\"" + ":";
+fn parse_amount_value(val: &str, tx_numb: usize, format: &AmountFormat) -> Result<u64, ParserError> {
+    format.parse(val.trim().trim_matches('"')).map_err(|source| ParserError::InvalidAmount { tx_numb, source })
+}
 
-    if json_vec[0] == "TX_TYPE" {
-        type_val = json_vec[1];
-        return Ok(key_str + "\"" + type_val.to_string().as_str() + "\"");
-    };
+fn parse_type_value(val: &str, tx_numb: usize) -> Result<TransactionType, ParserError> {
+    val.trim().parse::<TransactionType>().map_err(|source| ParserError::InvalidTransaction { tx_numb, source })
+}
 
-    if json_vec[0] == "STATUS" {
-        status_val = json_vec[1];
-        return Ok(key_str + "\"" + status_val.to_string().as_str() + "\"");
-    }  
+fn parse_status_value(val: &str, tx_numb: usize) -> Result<TransactionStatus, ParserError> {
+    val.trim().parse::<TransactionStatus>().map_err(|source| ParserError::InvalidTransaction { tx_numb, source })
+}
 
-    let key_val = "".to_owned() + json_vec[1];
-    Ok(key_str + key_val.as_str())
+/// Разбивает строку CSV на поля по запятым вне двойных кавычек. Кавычки в полях сохраняются.
+fn split_csv_line(line: &str) -> Vec<&str> {
+    let mut result: Vec<&str> = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (pos, ch) in line.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                result.push(line[start..pos].trim());
+                start = pos + 1;
+            }
+            _ => (),
+        }
+    }
+    result.push(line[start..].trim());
+    result
 }
 
 /// Функция сравнения двух транзакций
@@ -252,12 +568,9 @@ pub fn compare(tx_left: Transaction, tx_right: Transaction) -> bool {
 /// * left_side: Vec<Transaction> - второй набор транзакций
 /// 
 /// # Возвращаемое значение
-/// Возвращает true, если:
-/// * число транзакций содержащихся в наборах равно и
-/// * разность набора left_side и набора right_side равна 0 элементов и
-/// * разность набора right_side и набора left_side равна 0 элементов
-/// 
-/// иначе - false.
+/// Возвращает true, если каждая транзакция встречается в наборах одинаковое число раз
+/// (наборы сравниваются как мультимножества, порядок записей не важен),
+/// иначе - false. Подробности расхождений - см. [`diff::multiplicity_diff`].
 /// 
 /// # Пример
 /// ```ignore 
@@ -278,99 +591,18 @@ pub fn compare(tx_left: Transaction, tx_right: Transaction) -> bool {
 /// ```
 /// 
 pub fn compare_tx_sets(left_side: Vec<Transaction>, right_side: Vec<Transaction>) -> bool {
-
-    let mut result: bool = true;
-
-    result = result && (left_side.len() == right_side.len());
-
-    let left_hash: HashSet<Transaction> = left_side.into_iter().collect();
-    let right_hash: HashSet<Transaction> = right_side.into_iter().collect();
-
-    let left_difference: Vec<&Transaction> = left_hash.difference(&right_hash).collect();
-    let right_difference: Vec<&Transaction> = right_hash.difference(&left_hash).collect();
-
-    result = result && (left_difference.len() + right_difference.len() == 0);
-
-    result
+    left_side.len() == right_side.len() && diff::multiplicity_diff(&left_side, &right_side).is_empty()
 }
 
-/// Функция получения парметров командной строки при запуске утилит
+/// Функция получения типа формата по его строковому обозначению
 /// 
 /// # Аргументы
 /// 
-/// * args: Vec<String> - набор аргументов запуска утилит
-/// * is_file_creation: bool - флаг необходимости перезаписи (создания) файла 
-///   при проверки доступности записи файла по пути указанному в параметрепроверки
+/// * val: &str - обозначение формата: txt, csv, bin
 /// 
 /// # Возвращаемое значение
-/// Экземпляр CliParams содержащий парметры запуска утилит
-/// 
-/// # Пример
-/// ```ignore 
-/// // CMD:> util file1.csv csv bin file2.bin
-/// let args: Vec<String> = env::args().collect();
-///
-/// // pre-check 
-/// if args.len() < 5 {
-///     println!("{}", ERR_PARAMS_COMPARER);
-///     return Ok(());
-/// }
-///
-/// let params: CliParams = get_params(args, false);
-/// println!("{}", params.first_file_name);
-/// // file1.txt
-/// ```
-/// 
-/// # Ошибки
-/// panic - не поддерживаемые форматы, файл не существует или не может быть создан.
-/// 
-pub fn get_params(args: Vec<String>, is_file_creation: bool) -> CliParams {
-
-    let mut params = CliParams::new();
-
-    params.first_file_name = (&args[1]).to_owned();
-    params.first_file_format = get_format_value(&args[2]);
-
-    params.second_file_format = get_format_value(&args[3]);
-    params.second_file_name = (&args[4]).to_owned();
-    
-
-    if params.first_file_format == TransactionsFormatType::UNKNOWN || 
-        params.second_file_format == TransactionsFormatType::UNKNOWN {
-
-        panic!("{}", error::ERR_FORMAT)
-    }
-
-    let mut file_ok: bool; 
-
-    file_ok = Path::new(&params.first_file_name.trim()).exists();
-    if !file_ok {
-        panic!("File {} does not exist", params.first_file_name.trim());
-    }
-
-    if is_file_creation {
-        file_ok = check_file_creation(&params.second_file_name);
-        if !file_ok {
-            panic!("File {} could'nt create", params.second_file_name.trim());
-        }    
-    } 
-    else {
-        file_ok = Path::new(&params.second_file_name.trim()).exists();
-        if !file_ok {
-            panic!("File {} does not exist", params.second_file_name.trim());
-        }
-    }
-    params
-}
-
-fn check_file_creation(path: &str) -> bool {
-    match File::create(Path::new(path.trim())) {
-        Ok(_) => true,
-        Err(_) => false,
-    }
-}
-
-fn get_format_value(val: &str) -> TransactionsFormatType {
+/// Тип формата, TransactionsFormatType::UNKNOWN - для неподдерживаемого обозначения
+pub fn get_format_value(val: &str) -> TransactionsFormatType {
     match val {
         "txt" => TransactionsFormatType::TXT,
         "csv" => TransactionsFormatType::CSV,
@@ -408,17 +640,62 @@ pub fn get_parser_for_format(val: TransactionsFormatType) -> FormatParsers {
     }
 }
 
+/// Функция чтения набора транзакций из файла
+/// 
+/// # Аргументы
+/// 
+/// * file_name: &str - путь к файлу
+/// * format: TransactionsFormatType - формат файла
+/// 
+/// # Возвращаемое значение
+/// Набор транзакций, содержащихся в файле
+/// 
+/// # Ошибки
+/// ParserError - формат не поддерживается, ошибка чтения или парсинга файла.
+/// 
+pub fn read_tx_file(file_name: &str, format: TransactionsFormatType) -> Result<Vec<Transaction>, ParserError> {
+    if format == TransactionsFormatType::UNKNOWN {
+        return Err(ParserError::InvalidFormat);
+    }
+    let mut file = File::open(file_name.trim())?;
+    get_parser_for_format(format).from_read(&mut file)
+}
+
+/// Функция создания построителя транзакции для тестов модулей
+///
+/// Статус по умолчанию SUCCESS, время 0, описание пустое;
+/// значения переопределяются методами построителя.
+///
+/// # Пример
+/// ```ignore
+/// let tx = test_tx(1, TransactionType::TRANSFER, 10, 20, 100).timestamp(1000).build()?;
+/// ```
+#[cfg(test)]
+pub(crate) fn test_tx(tx_id: u64, tx_type: TransactionType, from_user_id: u64, to_user_id: u64,
+    amount: u64) -> TransactionBuilder {
+    Transaction::builder()
+        .tx_id(tx_id)
+        .tx_type(tx_type)
+        .from_user_id(from_user_id)
+        .to_user_id(to_user_id)
+        .amount(amount)
+        .timestamp(0)
+        .status(TransactionStatus::SUCCESS)
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::zero_prefixed_literal)]
 mod tests {
     use super::*;
+    use std::path::Path;
            
-    pub const TXT_EXAMPLE_IN_PATH: &str = "src\\example\\records_example.txt";
-    pub const CSV_EXAMPLE_IN_PATH: &str = "src\\example\\records_example.csv";
-    pub const BIN_EXAMPLE_IN_PATH: &str = "src\\example\\records_example.bin";
+    pub const TXT_EXAMPLE_IN_PATH: &str = "src/example/records_example.txt";
+    pub const CSV_EXAMPLE_IN_PATH: &str = "src/example/records_example.csv";
+    pub const BIN_EXAMPLE_IN_PATH: &str = "src/example/records_example.bin";
 
-    pub const TXT_EXAMPLE_OUT_PATH: &str = "src\\example\\test_txt.txt";
-    pub const CSV_EXAMPLE_OUT_PATH: &str = "src\\example\\test_csv.csv";
-    pub const BIN_EXAMPLE_OUT_PATH: &str = "src\\example\\test_bin.bin";
+    pub const TXT_EXAMPLE_OUT_PATH: &str = "src/example/test_txt.txt";
+    pub const CSV_EXAMPLE_OUT_PATH: &str = "src/example/test_csv.csv";
+    pub const BIN_EXAMPLE_OUT_PATH: &str = "src/example/test_bin.bin";
 
     #[test]
     fn test_new_tx_create() {
@@ -426,6 +703,86 @@ mod tests {
         assert_eq!(tx.tx_type, TransactionType::EMPTY);
     }
 
+    #[test]
+    fn test_builder_required_fields() {
+        let res = Transaction::builder()
+            .tx_id(1)
+            .tx_type(TransactionType::DEPOSIT)
+            .from_user_id(0)
+            .to_user_id(42)
+            .timestamp(0)
+            .status(TransactionStatus::SUCCESS)
+            .build();
+        assert_eq!(res, Err(BuildError::MissingField("AMOUNT")));
+    }
+
+    #[test]
+    fn test_builder_rejects_empty() {
+        let builder = Transaction::builder()
+            .tx_id(1)
+            .tx_type(TransactionType::EMPTY)
+            .from_user_id(0)
+            .to_user_id(42)
+            .amount(100)
+            .timestamp(0)
+            .status(TransactionStatus::SUCCESS);
+        assert_eq!(builder.clone().build(), Err(BuildError::UndefinedType));
+        assert_eq!(builder.allow_empty(true).build().map(|tx| tx.tx_type), Ok(TransactionType::EMPTY));
+    }
+
+    #[test]
+    fn test_read_unknown_type() {
+        let data = CVS_HEADER.to_owned() + "1,PAYMENT,0,42,100,0,SUCCESS,\"Unknown\"\n";
+        let parser = get_parser_for_format(TransactionsFormatType::CSV);
+        match parser.from_read(&mut data.as_bytes()) {
+            Err(ParserError::InvalidTransaction { tx_numb: 0, source }) => {
+                assert_eq!(source, BuildError::UnknownType("PAYMENT".to_owned()))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_empty_compat() -> Result<(), Box<dyn std::error::Error>> {
+        let data = "TX_ID: 1\nTX_TYPE: EMPTY\nFROM_USER_ID: 0\nTO_USER_ID: 42\nAMOUNT: 100\n\
+            TIMESTAMP: 0\nSTATUS: EMPTY\nDESCRIPTION: \"Legacy\"\n";
+        let parser = get_parser_for_format(TransactionsFormatType::TXT);
+        assert!(parser.from_read(&mut data.as_bytes()).is_err());
+
+        let res_vec = txt_format::TxtParser::with_empty_compat().from_read(&mut data.as_bytes())?;
+        assert_eq!(res_vec[0].status, TransactionStatus::EMPTY);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_fields() {
+        let tx_left = get_example_tx_set().remove(0);
+        let mut tx_right = tx_left.clone();
+        assert!(diff_fields(&tx_left, &tx_right).is_empty());
+
+        tx_right.amount = 200;
+        tx_right.status = TransactionStatus::FAILURE;
+        assert_eq!(diff_fields(&tx_left, &tx_right), vec![
+            FieldChange { field: TransactionField::Amount, before: "100".to_owned(), after: "200".to_owned() },
+            FieldChange { field: TransactionField::Status, before: "SUCCESS".to_owned(), after: "FAILURE".to_owned() },
+        ]);
+        assert_eq!("amount".parse::<TransactionField>().ok(), Some(TransactionField::Amount));
+    }
+
+    #[test]
+    fn test_field_json_names() -> Result<(), serde_json::Error> {
+        for field in TransactionField::ALL {
+            let json = serde_json::to_string(&field)?;
+            assert_eq!(json, format!("\"{}\"", field));
+            assert_eq!(serde_json::from_str::<TransactionField>(&json)?, field);
+        }
+        let change = FieldChange { field: TransactionField::TxId, before: "1".to_owned(), after: "2".to_owned() };
+        let json = serde_json::to_string(&change)?;
+        assert_eq!(json, r#"{"field":"TX_ID","before":"1","after":"2"}"#);
+        assert_eq!(serde_json::from_str::<FieldChange>(&json)?, change);
+        Ok(())
+    }
+
     #[test]
     fn test_format_parsers_create() {
         let parser = get_parser_for_format(TransactionsFormatType::BIN);
@@ -439,6 +796,14 @@ mod tests {
         assert_eq!(compare_tx_sets(left_side, right_side), true);
     }
 
+    #[test]
+    fn test_compare_tx_sets_multiplicity() {
+        let example = get_example_tx_set();
+        let (a, b) = (example[0].clone(), example[1].clone());
+        assert_eq!(compare_tx_sets(vec![a.clone(), a.clone(), b.clone()], vec![a.clone(), b.clone(), b.clone()]), false);
+        assert_eq!(compare_tx_sets(vec![b.clone(), a.clone(), a.clone()], vec![a.clone(), b, a]), true);
+    }
+
     #[test]
     fn test_get_format_value() {
         let fmt = String::from("txt");
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::{FieldChange, Transaction, TransactionField, TransactionStatus, diff_fields};
+
+/// Поля, которые не могут меняться между записями одной транзакции
+pub const IMMUTABLE_FIELDS: [TransactionField; 4] = [
+    TransactionField::TxType,
+    TransactionField::FromUserId,
+    TransactionField::ToUserId,
+    TransactionField::Amount,
+];
+
+/// Недопустимый переход статуса транзакции
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IllegalTransition {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - position – номер записи в потоке
+    pub position: usize,
+    /// - from – текущий статус транзакции
+    pub from: TransactionStatus,
+    /// - to – статус в отклоненной записи
+    pub to: TransactionStatus,
+}
+
+/// Конфликтующее изменение неизменяемых полей транзакции
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldConflict {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - position – номер записи в потоке
+    pub position: usize,
+    /// - changes – изменения неизменяемых полей относительно текущего состояния
+    pub changes: Vec<FieldChange>,
+}
+
+/// Результат объединения потока записей о транзакциях
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct LifecycleReport {
+    /// - merged – последнее состояние каждой транзакции в порядке первого появления tx_id
+    pub merged: Vec<Transaction>,
+    /// - illegal – отклоненные записи с недопустимым переходом статуса
+    pub illegal: Vec<IllegalTransition>,
+    /// - conflicts – отклоненные записи с изменением неизменяемых полей
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// Функция проверки допустимости перехода статуса транзакции
+///
+/// Допустимы повтор текущего статуса и переход из PENDING в SUCCESS или FAILURE.
+/// Статусы SUCCESS и FAILURE являются конечными. Переход из неопределенного статуса (EMPTY)
+/// допустим в любой статус, переход в EMPTY - только из EMPTY.
+pub fn is_legal_transition(from: &TransactionStatus, to: &TransactionStatus) -> bool {
+    from == to || matches!(
+        (from, to),
+        (TransactionStatus::PENDING, TransactionStatus::SUCCESS)
+            | (TransactionStatus::PENDING, TransactionStatus::FAILURE)
+            | (TransactionStatus::EMPTY, _)
+    )
+}
+
+/// Функция объединения упорядоченного потока записей в последнее состояние каждой транзакции
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - записи в порядке поступления
+///
+/// # Возвращаемое значение
+/// LifecycleReport. Запись с недопустимым переходом статуса или с изменением неизменяемых полей
+/// (тип, участники, сумма) отклоняется и попадает в отчет, состояние транзакции при этом не меняется.
+/// Время и описание принимаются из последней принятой записи.
+///
+/// # Пример
+/// ```ignore
+/// let report = merge_lifecycle(&tx_vec);
+/// for illegal in report.illegal {
+///     println!("{}: {} -> {}", illegal.tx_id, illegal.from, illegal.to);
+/// }
+/// ```
+pub fn merge_lifecycle(data: &[Transaction]) -> LifecycleReport {
+    let mut report = LifecycleReport::default();
+    let mut index: HashMap<u64, usize> = HashMap::new();
+
+    for (position, tx) in data.iter().enumerate() {
+        let current = match index.get(&tx.tx_id) {
+            Some(idx) => &mut report.merged[*idx],
+            None => {
+                index.insert(tx.tx_id, report.merged.len());
+                report.merged.push(tx.clone());
+                continue;
+            }
+        };
+
+        if !is_legal_transition(&current.status, &tx.status) {
+            report.illegal.push(IllegalTransition {
+                tx_id: tx.tx_id,
+                position,
+                from: current.status.clone(),
+                to: tx.status.clone(),
+            });
+            continue;
+        }
+
+        let changes: Vec<FieldChange> = diff_fields(current, tx)
+            .into_iter()
+            .filter(|change| IMMUTABLE_FIELDS.contains(&change.field))
+            .collect();
+        if !changes.is_empty() {
+            report.conflicts.push(FieldConflict { tx_id: tx.tx_id, position, changes });
+            continue;
+        }
+
+        *current = tx.clone();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    #[test]
+    fn test_merge_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).status(TransactionStatus::PENDING).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 300).timestamp(1).status(TransactionStatus::PENDING).build()?,
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).timestamp(2).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 300).timestamp(3).status(TransactionStatus::FAILURE).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 300).timestamp(4).status(TransactionStatus::PENDING).build()?,
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 150).timestamp(5).build()?,
+        ];
+        let report = merge_lifecycle(&data);
+
+        assert_eq!(report.merged, vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).timestamp(2).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 300).timestamp(3).status(TransactionStatus::FAILURE).build()?,
+        ]);
+        assert_eq!(report.illegal, vec![IllegalTransition {
+            tx_id: 2, position: 4, from: TransactionStatus::FAILURE, to: TransactionStatus::PENDING,
+        }]);
+        assert_eq!(report.conflicts, vec![FieldConflict {
+            tx_id: 1,
+            position: 5,
+            changes: vec![FieldChange { field: TransactionField::Amount, before: "100".to_owned(), after: "150".to_owned() }],
+        }]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_legal_transition() {
+        assert!(is_legal_transition(&TransactionStatus::PENDING, &TransactionStatus::SUCCESS));
+        assert!(is_legal_transition(&TransactionStatus::SUCCESS, &TransactionStatus::SUCCESS));
+        assert!(!is_legal_transition(&TransactionStatus::SUCCESS, &TransactionStatus::FAILURE));
+        assert!(!is_legal_transition(&TransactionStatus::FAILURE, &TransactionStatus::PENDING));
+    }
+}
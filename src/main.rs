@@ -1,26 +1,511 @@
-use std::{env, fs::File, path::Path};
-use bis_rust::{CliParams, Transaction, TransactionsParser, 
-    error::{ERR_PARAMS_CONVERTER, ParserError}, get_params, get_parser_for_format};
+use std::{env, fs::File, io::{ErrorKind, Write}, path::Path, process::ExitCode};
 
-fn main() -> Result<(), ParserError> {
+use bis_rust::{Transaction, TransactionField, TransactionsParser,
+    aggregate::{AggregateReport, DEFAULT_AGGREGATE_BY, reconcile_aggregates, write_aggregate_json},
+    amount::Rounding,
+    cli::{CliArgs, Flag, USAGE},
+    delta::{DeltaEntry, DeltaParser, apply_delta, make_delta},
+    diff::{CompareOptions, DiffReport, MultiplicityDiff, diff_tx_sets_with, multiplicity_diff, write_diff_json},
+    duplicates::{DEFAULT_REPLAY_WINDOW_MS, find_duplicates},
+    error::ParserError,
+    external::{ExternalSortOptions, TxReader, diff_tx_files},
+    fx::{RateTable, RatesParser, convert_transactions},
+    get_parser_for_format,
+    ledger::{Ledger, write_balances_csv},
+    matching::{MatchReport, MatchScoring, fuzzy_match, write_match_json},
+    ordered::{EditKind, OrderedDiff, ordered_diff, write_ordered_json},
+    read_tx_file,
+    stats::{GroupBy, StatsOutput, compute_stats, write_stats},
+    threeway::{MergeStatus, merge3}};
 
-    let args: Vec<String> = env::args().collect(); 
+/// Код завершения: успешное выполнение (наборы совпадают, файл корректен)
+const EXIT_OK: u8 = 0;
+/// Код завершения: наборы различаются, файл некорректен, найдены дубликаты или конфликты объединения
+const EXIT_DIFFERENT: u8 = 1;
+/// Код завершения: ошибка параметров, чтения или парсинга
+const EXIT_ERROR: u8 = 2;
 
-    if args.len() < 5 {
-        println!("{}", ERR_PARAMS_CONVERTER);
+const CONVERT_FLAGS: [Flag; 8] = [
+    Flag::value("input").short("i"), Flag::value("output").short("o"), Flag::value("from"), Flag::value("to"),
+    Flag::value("rates"), Flag::value("source-currency"), Flag::value("reporting-currency"), Flag::value("rounding"),
+];
+
+const COMPARE_FLAGS: [Flag; 17] = [
+    Flag::value("left").short("l"), Flag::value("right").short("r"), Flag::value("left-format"),
+    Flag::value("right-format"), Flag::value("key"), Flag::value("ignore"), Flag::value("timestamp-tolerance"),
+    Flag::switch("ignore-case"), Flag::switch("normalize-whitespace"), Flag::switch("ordered"),
+    Flag::switch("external"), Flag::value("run-size"), Flag::switch("fuzzy"), Flag::value("scoring"),
+    Flag::switch("aggregate"), Flag::value("by"), Flag::value("report"),
+];
+
+const VALIDATE_FLAGS: [Flag; 2] = [Flag::value("input").short("i"), Flag::value("from")];
+
+const STATS_FLAGS: [Flag; 4] = [Flag::value("input").short("i"), Flag::value("from"), Flag::value("by"),
+    Flag::value("report")];
+
+const BALANCES_FLAGS: [Flag; 3] = [Flag::value("input").short("i"), Flag::value("output").short("o"),
+    Flag::value("from")];
+
+const DUPLICATES_FLAGS: [Flag; 3] = [Flag::value("input").short("i"), Flag::value("from"),
+    Flag::value("replay-window")];
+
+const MERGE3_FLAGS: [Flag; 8] = [
+    Flag::value("base"), Flag::value("left").short("l"), Flag::value("right").short("r"),
+    Flag::value("output").short("o"), Flag::value("base-format"), Flag::value("left-format"),
+    Flag::value("right-format"), Flag::value("to"),
+];
+
+const DIFF_FLAGS: [Flag; 5] = [Flag::value("base"), Flag::value("new"), Flag::value("output").short("o"),
+    Flag::value("base-format"), Flag::value("new-format")];
+
+const PATCH_FLAGS: [Flag; 5] = [Flag::value("base"), Flag::value("delta"), Flag::value("output").short("o"),
+    Flag::value("base-format"), Flag::value("to")];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+
+    let Some(command) = args.get(1) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(EXIT_ERROR);
+    };
+    let flags = &args[2..];
+
+    let result = match command.as_str() {
+        "convert" => CliArgs::parse(flags, &CONVERT_FLAGS).and_then(|args| convert(&args)),
+        "compare" => CliArgs::parse(flags, &COMPARE_FLAGS).and_then(|args| compare(&args)),
+        "validate" => CliArgs::parse(flags, &VALIDATE_FLAGS).and_then(|args| validate(&args)),
+        "stats" => CliArgs::parse(flags, &STATS_FLAGS).and_then(|args| stats(&args)),
+        "balances" => CliArgs::parse(flags, &BALANCES_FLAGS).and_then(|args| balances(&args)),
+        "duplicates" => CliArgs::parse(flags, &DUPLICATES_FLAGS).and_then(|args| duplicates(&args)),
+        "merge3" => CliArgs::parse(flags, &MERGE3_FLAGS).and_then(|args| merge(&args)),
+        "diff" => CliArgs::parse(flags, &DIFF_FLAGS).and_then(|args| delta_diff(&args)),
+        "patch" => CliArgs::parse(flags, &PATCH_FLAGS).and_then(|args| delta_patch(&args)),
+        "help" | "--help" | "-h" => {
+            writeln!(std::io::stdout(), "{}", USAGE).map(|_| true).map_err(ParserError::from)
+        }
+        _ => Err(ParserError::InvalidArgs(format!("Unknown command: {}", command))),
+    };
+
+    match result {
+        Ok(true) => ExitCode::from(EXIT_OK),
+        // Получатель вывода закрыт раньше времени (например, `bis compare ... | head`)
+        Err(ParserError::Io(e)) if e.kind() == ErrorKind::BrokenPipe => ExitCode::from(EXIT_OK),
+        Ok(false) => ExitCode::from(EXIT_DIFFERENT),
+        Err(e @ ParserError::InvalidArgs(_)) => {
+            eprintln!("{}\nRun `bis help` for usage.", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// Чтение набора транзакций из файла флага name, формат - флаг format_name или расширение файла
+fn read_input(args: &CliArgs, name: &str, format_name: &str) -> Result<Vec<Transaction>, ParserError> {
+    let file_name = args.required(name)?;
+    read_tx_file(file_name, args.format(format_name, file_name)?)
+}
+
+/// Запись набора транзакций в файл флага output, формат - флаг format_name или расширение файла
+fn write_output(args: &CliArgs, format_name: &str, data: &[Transaction]) -> Result<(), ParserError> {
+    let file_name = args.required("output")?;
+    let format = args.format(format_name, file_name)?;
+    let mut out_file = File::create(Path::new(file_name))?;
+    get_parser_for_format(format).write_to(&mut out_file, data)
+}
+
+fn convert(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut res_vec: Vec<Transaction> = read_input(args, "input", "from")?;
+
+    let rates = ["rates", "source-currency", "reporting-currency"];
+    if rates.iter().any(|name| args.is_set(name)) || args.is_set("rounding") {
+        let [rates_file, from, to] = rates.map(|name| args.required(name));
+        let rounding = args.parsed::<Rounding>("rounding")?.unwrap_or(Rounding::HalfEven);
+        let table = RateTable::new(RatesParser::default().from_read(&mut File::open(rates_file?)?)?);
+        res_vec = convert_transactions(&res_vec, from?, to?, &table, rounding)?;
+    }
+
+    write_output(args, "to", &res_vec)?;
+    Ok(true)
+}
+
+fn validate(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let file_name = args.required("input")?;
+    let mut count: usize = 0;
+    for tx in TxReader::open(file_name, args.format("from", file_name)?)? {
+        match tx {
+            Ok(_) => count += 1,
+            Err(ParserError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                writeln!(out, "Invalid: record is truncated ({} valid records before)", count)?;
+                return Ok(false);
+            }
+            Err(e @ ParserError::Io(_)) => return Err(e),
+            Err(e) => {
+                writeln!(out, "Invalid: {} ({} valid records before)", e, count)?;
+                return Ok(false);
+            }
+        }
+    }
+    writeln!(out, "Valid: {} records.", count)?;
+    Ok(true)
+}
+
+fn stats(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let group_by = args.parsed::<GroupBy>("by")?.unwrap_or(GroupBy::All);
+    let output = args.parsed::<StatsOutput>("report")?.unwrap_or(StatsOutput::Table);
+    let res_vec: Vec<Transaction> = read_input(args, "input", "from")?;
+
+    write_stats(&mut out, &compute_stats(&res_vec, group_by), output)?;
+    Ok(true)
+}
+
+fn balances(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let res_vec: Vec<Transaction> = read_input(args, "input", "from")?;
+    let out_name = args.required("output")?;
+
+    let report = Ledger::new().apply_all(&res_vec);
+
+    for overdraft in &report.overdrafts {
+        writeln!(out, "Overdraft: user {} balance {} after transaction {}",
+            overdraft.user_id, overdraft.balance, overdraft.tx_id)?;
+    }
+    for overflow in &report.overflows {
+        writeln!(out, "Skipped: {}", overflow)?;
+    }
+
+    let mut out_file = File::create(Path::new(out_name))?;
+    write_balances_csv(&mut out_file, &report.balances)?;
+    Ok(true)
+}
+
+fn duplicates(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let replay_window_ms = args.parsed::<u64>("replay-window")?.unwrap_or(DEFAULT_REPLAY_WINDOW_MS);
+    let res_vec: Vec<Transaction> = read_input(args, "input", "from")?;
+
+    let report = find_duplicates(&res_vec, replay_window_ms);
+
+    if report.is_empty() {
+        writeln!(out, "No duplicates found.")?;
+        return Ok(true);
+    }
+
+    for duplicate in &report.exact {
+        writeln!(out, "Exact duplicate: tx {} occurs {} times at records {:?}",
+            duplicate.tx.tx_id, duplicate.positions.len(), duplicate.positions)?;
+    }
+    for conflict in &report.conflicts {
+        writeln!(out, "Conflict: tx {} records {} and {} differ:", conflict.tx_id, conflict.first, conflict.other)?;
+        for change in &conflict.changes {
+            writeln!(out, "  {}: {} -> {}", change.field, change.before, change.after)?;
+        }
+    }
+    for replay in &report.replays {
+        writeln!(out, "Probable replay: tx {} repeats tx {} after {} ms",
+            replay.replay_tx_id, replay.original_tx_id, replay.delta_ms)?;
+    }
+    Ok(false)
+}
+
+fn merge(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let base: Vec<Transaction> = read_input(args, "base", "base-format")?;
+    let res_vec_left: Vec<Transaction> = read_input(args, "left", "left-format")?;
+    let res_vec_right: Vec<Transaction> = read_input(args, "right", "right-format")?;
+
+    let report = merge3(&base, &res_vec_left, &res_vec_right);
+
+    for status in [MergeStatus::Unchanged, MergeStatus::LeftChanged, MergeStatus::RightChanged,
+        MergeStatus::BothSame, MergeStatus::Conflict] {
+        writeln!(out, "{}: {}", status, report.count(status))?;
+    }
+    for conflict in &report.conflicts {
+        writeln!(out, "Conflict: tx {}", conflict.tx_id)?;
+        for (side, records) in [("base", &conflict.base), (args.required("left")?, &conflict.left),
+            (args.required("right")?, &conflict.right)] {
+            if records.is_empty() {
+                writeln!(out, "  {}: absent", side)?;
+            }
+            for tx in records {
+                writeln!(out, "  {}: {}", side, tx_line(tx))?;
+            }
+        }
+    }
+
+    write_output(args, "to", &report.merged)?;
+    Ok(report.conflicts.is_empty())
+}
+
+fn delta_diff(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let base: Vec<Transaction> = read_input(args, "base", "base-format")?;
+    let res_vec: Vec<Transaction> = read_input(args, "new", "new-format")?;
+    let delta: Vec<DeltaEntry> = make_delta(&base, &res_vec);
+
+    let mut out_file = File::create(Path::new(args.required("output")?))?;
+    DeltaParser::default().write_to(&mut out_file, &delta)?;
+    writeln!(out, "{} changes written.", delta.len())?;
+    Ok(true)
+}
+
+fn delta_patch(args: &CliArgs) -> Result<bool, ParserError> {
+    let base: Vec<Transaction> = read_input(args, "base", "base-format")?;
+    let delta: Vec<DeltaEntry> = DeltaParser::default().from_read(&mut File::open(args.required("delta")?)?)?;
+    let res_vec: Vec<Transaction> = apply_delta(&base, &delta)?;
+
+    write_output(args, "to", &res_vec)?;
+    Ok(true)
+}
+
+/// Формат вывода результата сравнения
+#[derive(PartialEq)]
+enum Output {
+    Text,
+    Json,
+}
+
+/// Сравнение наборов, возвращает true, если наборы совпадают
+fn compare(args: &CliArgs) -> Result<bool, ParserError> {
+    let mut out = std::io::stdout().lock();
+    let invalid_args = |msg: &str| ParserError::InvalidArgs(msg.to_owned());
+
+    let output = match args.value("report") {
+        None | Some("text") => Output::Text,
+        Some("json") => Output::Json,
+        Some(val) => return Err(ParserError::InvalidArgs(format!("Invalid value of --report: {}", val))),
+    };
+
+    let modes = ["ordered", "external", "fuzzy", "aggregate"];
+    if modes.iter().filter(|mode| args.is_set(mode)).count() > 1 {
+        return Err(invalid_args("Use only one of --ordered, --external, --fuzzy, --aggregate"));
+    }
+    for (flag, mode) in [("run-size", "external"), ("scoring", "fuzzy"), ("by", "aggregate")] {
+        if args.is_set(flag) && !args.is_set(mode) {
+            return Err(ParserError::InvalidArgs(format!("Flag --{} requires --{}", flag, mode)));
+        }
+    }
+
+    let mut options = CompareOptions::default();
+    let fields = |name: &str| -> Result<Option<Vec<TransactionField>>, ParserError> {
+        args.value(name)
+            .map(|val| {
+                val.split(',')
+                    .map(|field| field.parse::<TransactionField>()
+                        .map_err(|_| ParserError::InvalidArgs(format!("Invalid value of --{}: {}", name, field))))
+                    .collect()
+            })
+            .transpose()
+    };
+    if let Some(key_fields) = fields("key")? {
+        options.key_fields = key_fields;
+    }
+    if let Some(ignore_fields) = fields("ignore")? {
+        options.ignore_fields = ignore_fields;
+    }
+    options.timestamp_tolerance_ms = args.parsed::<u64>("timestamp-tolerance")?.unwrap_or_default();
+    options.ignore_case = args.is_set("ignore-case");
+    options.normalize_whitespace = args.is_set("normalize-whitespace");
+
+    let left_name = args.required("left")?;
+    let right_name = args.required("right")?;
+    let left_format = args.format("left-format", left_name)?;
+    let right_format = args.format("right-format", right_name)?;
+
+    if args.is_set("external") {
+        let mut sort_options = ExternalSortOptions::default();
+        if let Some(run_size) = args.parsed::<usize>("run-size")? {
+            sort_options.run_size = run_size;
+        }
+        if sort_options.run_size == 0 {
+            return Err(invalid_args("Invalid value of --run-size: 0"));
+        }
+        let diff = diff_tx_files(left_name, left_format, right_name, right_format, &options, &sort_options)?;
+        match output {
+            Output::Json => write_diff_json(&mut out, &diff.report, diff.left_total, diff.right_total)?,
+            Output::Text => print_report(&mut out, &diff.report, &diff.multiplicity, left_name, right_name)?,
+        }
+        return Ok(diff.report.is_empty());
+    }
+
+    let res_vec_left: Vec<Transaction> = read_tx_file(left_name, left_format)?;
+    let res_vec_right: Vec<Transaction> = read_tx_file(right_name, right_format)?;
+
+    if args.is_set("aggregate") {
+        let group_by = match args.value("by") {
+            Some(val) => val.split(',').map(|group_by| group_by.trim().parse::<GroupBy>())
+                .collect::<Result<Vec<GroupBy>, ParserError>>()?,
+            None => DEFAULT_AGGREGATE_BY.to_vec(),
+        };
+        let report = reconcile_aggregates(&res_vec_left, &res_vec_right, &group_by);
+        match output {
+            Output::Json => write_aggregate_json(&mut out, &report)?,
+            Output::Text => print_aggregates(&mut out, &report)?,
+        }
+        return Ok(report.is_empty());
+    }
+
+    if args.is_set("fuzzy") {
+        let scoring = match args.value("scoring") {
+            Some(file_name) => MatchScoring::from_read(&mut File::open(file_name)?)?,
+            None => MatchScoring::default(),
+        };
+        let report = fuzzy_match(&res_vec_left, &res_vec_right, &scoring);
+        match output {
+            Output::Json => write_match_json(&mut out, &report)?,
+            Output::Text => print_matches(&mut out, &report, left_name, right_name)?,
+        }
+        return Ok(report.is_complete());
+    }
+
+    if args.is_set("ordered") {
+        let diff = ordered_diff(&res_vec_left, &res_vec_right, &options);
+        match output {
+            Output::Json => write_ordered_json(&mut out, &diff)?,
+            Output::Text => print_ordered(&mut out, &diff)?,
+        }
+        return Ok(diff.is_empty());
+    }
+
+    let report = diff_tx_sets_with(&res_vec_left, &res_vec_right, &options);
+
+    match output {
+        Output::Json => write_diff_json(&mut out, &report, res_vec_left.len(), res_vec_right.len())?,
+        Output::Text => print_report(&mut out, &report, &multiplicity_diff(&res_vec_left, &res_vec_right), left_name,
+            right_name)?,
+    }
+
+    Ok(report.is_empty())
+}
+
+fn print_report<W: Write>(out: &mut W, report: &DiffReport, multiplicity: &[MultiplicityDiff], left_name: &str, right_name: &str) -> Result<(), ParserError> {
+    if report.is_empty() {
+        writeln!(out, "Transactions sets are the same.")?;
         return Ok(());
     }
-    
-    let params: CliParams = get_params(args, true);
 
-    let in_parser = get_parser_for_format(params.first_file_format);
-    let mut file = File::open(params.first_file_name.trim())?;
-    let res_vec: Vec<Transaction> = in_parser.from_read(&mut file)?;
+    writeln!(out, "Transactions sets are NOT the same.")?;
+    writeln!(out, "Only in {} ({}):", left_name, report.only_left.len())?;
+    for tx in &report.only_left {
+        writeln!(out, "  {}", tx_line(tx))?;
+    }
+    writeln!(out, "Only in {} ({}):", right_name, report.only_right.len())?;
+    for tx in &report.only_right {
+        writeln!(out, "  {}", tx_line(tx))?;
+    }
+    let duplicated: Vec<_> = multiplicity
+        .iter()
+        .filter(|diff| diff.left_count > 0 && diff.right_count > 0)
+        .collect();
+    if !duplicated.is_empty() {
+        writeln!(out, "Occurrence count differs ({}):", duplicated.len())?;
+        for diff in &duplicated {
+            writeln!(out, "  tx {}: {} in {}, {} in {}", diff.tx.tx_id, diff.left_count, left_name,
+                diff.right_count, right_name)?;
+        }
+    }
+    writeln!(out, "Changed ({}):", report.changed.len())?;
+    for changed in &report.changed {
+        writeln!(out, "  tx {}:", changed.tx_id)?;
+        for change in &changed.changes {
+            writeln!(out, "    {}: {} -> {}", change.field, change.before, change.after)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_ordered<W: Write>(out: &mut W, diff: &OrderedDiff) -> Result<(), ParserError> {
+    let Some(first) = diff.first_divergence() else {
+        writeln!(out, "Transactions sequences are the same.")?;
+        return Ok(());
+    };
 
-    let out_parser = get_parser_for_format(params.second_file_format);
+    writeln!(out, "Transactions sequences are NOT the same.")?;
+    writeln!(out, "First divergence at record {} (first file), record {} (second file).", first.left_pos,
+        first.right_pos)?;
+    writeln!(out, "Edits ({}):", diff.edits.len())?;
+    for edit in &diff.edits {
+        writeln!(out, "  {} at {}/{}", edit.kind, edit.left_pos, edit.right_pos)?;
+        match edit.kind {
+            EditKind::Delete => {
+                if let Some(tx) = &edit.left {
+                    writeln!(out, "    - {}", tx_line(tx))?;
+                }
+            }
+            EditKind::Insert => {
+                if let Some(tx) = &edit.right {
+                    writeln!(out, "    + {}", tx_line(tx))?;
+                }
+            }
+            EditKind::Substitute => {
+                for change in &edit.changes {
+                    writeln!(out, "    {}: {} -> {}", change.field, change.before, change.after)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
-    let mut out_file = File::create(Path::new(params.second_file_name.trim()))?;
-    out_parser.write_to(&mut out_file, &res_vec)?;
+fn print_matches<W: Write>(out: &mut W, report: &MatchReport, left_name: &str, right_name: &str) -> Result<(), ParserError> {
+    if report.is_complete() {
+        writeln!(out, "All {} transactions are matched.", report.matched.len())?;
+        return Ok(());
+    }
 
+    writeln!(out, "Transactions sets are NOT fully matched.")?;
+    writeln!(out, "Matched ({}):", report.matched.len())?;
+    for pair in &report.matched {
+        writeln!(out, "  tx {} <-> tx {} (score {})", pair.left.tx_id, pair.right.tx_id, pair.score)?;
+    }
+    writeln!(out, "Ambiguous ({}):", report.ambiguous.len())?;
+    for ambiguous in &report.ambiguous {
+        let candidates: Vec<String> = ambiguous.candidates
+            .iter()
+            .map(|candidate| format!("tx {} (score {})", candidate.tx.tx_id, candidate.score))
+            .collect();
+        writeln!(out, "  tx {} -> {}", ambiguous.left.tx_id, candidates.join(", "))?;
+    }
+    writeln!(out, "Unmatched in {} ({}):", left_name, report.unmatched_left.len())?;
+    for tx in &report.unmatched_left {
+        writeln!(out, "  {}", tx_line(tx))?;
+    }
+    writeln!(out, "Unmatched in {} ({}):", right_name, report.unmatched_right.len())?;
+    for tx in &report.unmatched_right {
+        writeln!(out, "  {}", tx_line(tx))?;
+    }
     Ok(())
-} 
\ No newline at end of file
+}
+
+fn print_aggregates<W: Write>(out: &mut W, report: &AggregateReport) -> Result<(), ParserError> {
+    writeln!(out, "Totals: {} transactions, amount {} -> {} transactions, amount {}", report.left.count,
+        report.left.total, report.right.count, report.right.total)?;
+    if report.is_empty() {
+        writeln!(out, "All {} buckets are the same.", report.buckets)?;
+        return Ok(());
+    }
+
+    writeln!(out, "Buckets differ ({} of {}):", report.differences.len(), report.buckets)?;
+    for diff in &report.differences {
+        let key: Vec<String> = report.group_by
+            .iter()
+            .zip(&diff.key)
+            .map(|(group_by, val)| format!("{}={}", group_by, val))
+            .collect();
+        writeln!(out, "  {}: count {} -> {} ({:+}), amount {} -> {} ({:+})", key.join(" "), diff.left.count,
+            diff.right.count, diff.count_delta, diff.left.total, diff.right.total, diff.total_delta)?;
+    }
+    Ok(())
+}
+
+fn tx_line(tx: &Transaction) -> String {
+    TransactionField::ALL
+        .into_iter()
+        .map(|field| format!("{}={}", field, field.value_of(tx)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
@@ -0,0 +1,359 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Transaction, error::ParserError};
+
+/// Правила оценки сходства записей с разными tx_id
+///
+/// Кандидатами считаются записи, время которых отличается не более чем на timestamp_window_ms,
+/// сумма - не более чем на amount_tolerance, а совпадающих участников не меньше min_parties. Оценка кандидата складывается из весов совпадения
+/// участников, суммы и времени (веса суммы и времени уменьшаются пропорционально расхождению).
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(default)]
+pub struct MatchScoring {
+    /// - parties_weight – вес совпадения участников (половина за отправителя, половина за получателя)
+    pub parties_weight: u64,
+    /// - amount_weight – вес совпадения суммы
+    pub amount_weight: u64,
+    /// - time_weight – вес совпадения времени
+    pub time_weight: u64,
+    /// - amount_tolerance – допустимое расхождение суммы, минимальные единицы
+    pub amount_tolerance: u64,
+    /// - timestamp_window_ms – допустимое расхождение времени, мс
+    pub timestamp_window_ms: u64,
+    /// - require_same_type – кандидаты должны иметь одинаковый тип транзакции
+    pub require_same_type: bool,
+    /// - min_parties – минимальное число совпадающих участников (0, 1 или 2)
+    pub min_parties: u64,
+    /// - min_score – минимальная оценка кандидата
+    pub min_score: u64,
+    /// - ambiguity_margin – кандидаты, оценки которых отличаются не более чем на эту величину, неразличимы
+    pub ambiguity_margin: u64,
+}
+
+impl Default for MatchScoring {
+    fn default() -> Self {
+        Self {
+            parties_weight: 40,
+            amount_weight: 40,
+            time_weight: 20,
+            amount_tolerance: 0,
+            timestamp_window_ms: 60_000,
+            require_same_type: true,
+            min_parties: 1,
+            min_score: 60,
+            ambiguity_margin: 0,
+        }
+    }
+}
+
+impl MatchScoring {
+    /// Функция чтения правил в формате JSON
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    pub fn from_read<R: std::io::Read>(source: &mut R) -> Result<Self, ParserError> {
+        Ok(serde_json::from_reader(source)?)
+    }
+
+    /// Функция оценки сходства двух записей
+    ///
+    /// # Возвращаемое значение
+    /// Оценка, None - записи не являются кандидатами или оценка ниже min_score
+    pub fn score(&self, tx_left: &Transaction, tx_right: &Transaction) -> Option<u64> {
+        let time_delta = tx_left.timestamp.abs_diff(tx_right.timestamp);
+        let amount_delta = tx_left.amount.abs_diff(tx_right.amount);
+        if time_delta > self.timestamp_window_ms || amount_delta > self.amount_tolerance
+            || (self.require_same_type && tx_left.tx_type != tx_right.tx_type) {
+            return None;
+        }
+
+        let parties = [tx_left.from_user_id == tx_right.from_user_id, tx_left.to_user_id == tx_right.to_user_id]
+            .into_iter()
+            .filter(|same| *same)
+            .count() as u64;
+        if parties < self.min_parties {
+            return None;
+        }
+        let score = (self.parties_weight.saturating_mul(parties) / 2)
+            .saturating_add(proportional(self.amount_weight, amount_delta, self.amount_tolerance))
+            .saturating_add(proportional(self.time_weight, time_delta, self.timestamp_window_ms));
+
+        (score >= self.min_score).then_some(score)
+    }
+}
+
+/// Вес, уменьшенный пропорционально расхождению delta при допуске tolerance
+fn proportional(weight: u64, delta: u64, tolerance: u64) -> u64 {
+    let scale = tolerance as u128 + 1;
+    (weight as u128 * (scale - delta as u128) / scale) as u64
+}
+
+/// Сопоставленная пара записей
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct FuzzyMatch {
+    /// - left – запись первого набора
+    pub left: Transaction,
+    /// - right – запись второго набора
+    pub right: Transaction,
+    /// - score – оценка сходства
+    pub score: u64,
+}
+
+/// Кандидат для сопоставления
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct MatchCandidate {
+    /// - tx – запись второго набора
+    pub tx: Transaction,
+    /// - score – оценка сходства
+    pub score: u64,
+}
+
+/// Запись первого набора, для которой нельзя однозначно выбрать пару
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct AmbiguousMatch {
+    /// - left – запись первого набора
+    pub left: Transaction,
+    /// - candidates – кандидаты, упорядочены по убыванию оценки
+    pub candidates: Vec<MatchCandidate>,
+}
+
+/// Результат нечеткого сопоставления наборов
+#[derive(Serialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct MatchReport {
+    /// - matched – сопоставленные пары
+    pub matched: Vec<FuzzyMatch>,
+    /// - ambiguous – записи первого набора с неразличимыми кандидатами
+    pub ambiguous: Vec<AmbiguousMatch>,
+    /// - unmatched_left – записи первого набора без кандидатов
+    pub unmatched_left: Vec<Transaction>,
+    /// - unmatched_right – записи второго набора, не сопоставленные и не являющиеся кандидатами
+    pub unmatched_right: Vec<Transaction>,
+}
+
+impl MatchReport {
+    /// Возвращает true, если все записи обоих наборов сопоставлены
+    pub fn is_complete(&self) -> bool {
+        self.ambiguous.is_empty() && self.unmatched_left.is_empty() && self.unmatched_right.is_empty()
+    }
+}
+
+#[derive(Serialize)]
+struct MatchDocument<'a> {
+    complete: bool,
+    #[serde(flatten)]
+    report: &'a MatchReport,
+}
+
+/// Функция записи результата нечеткого сопоставления в формате JSON
+///
+/// Документ содержит признак полного сопоставления наборов (complete)
+/// и списки matched, ambiguous, unmatched_left, unmatched_right.
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер, stdout)
+/// * report - результат сопоставления
+pub fn write_match_json<W: std::io::Write>(target: &mut W, report: &MatchReport) -> Result<(), ParserError> {
+    serde_json::to_writer_pretty(&mut *target, &MatchDocument { complete: report.is_complete(), report })?;
+    target.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Функция нечеткого сопоставления наборов транзакций без учета tx_id
+///
+/// Для каждой записи первого набора подбираются кандидаты второго набора по правилам scoring.
+/// Пара сопоставляется, если записи являются лучшими кандидатами друг для друга и оценка остальных
+/// кандидатов обеих записей ниже на величину больше ambiguity_margin. Сопоставленные записи исключаются,
+/// и подбор повторяется, пока появляются новые пары. Оставшиеся записи первого набора с кандидатами
+/// попадают в ambiguous, остальные - в unmatched_left и unmatched_right.
+///
+/// # Аргументы
+///
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+/// * scoring: &MatchScoring - правила оценки сходства
+///
+/// # Возвращаемое значение
+/// MatchReport, пары упорядочены по записи первого набора
+///
+/// # Пример
+/// ```ignore
+/// let scoring = MatchScoring { amount_tolerance: 100, ..Default::default() };
+/// let report = fuzzy_match(&our_vec, &partner_vec, &scoring);
+/// println!("{} matched, {} ambiguous", report.matched.len(), report.ambiguous.len());
+/// ```
+pub fn fuzzy_match(left_side: &[Transaction], right_side: &[Transaction], scoring: &MatchScoring) -> MatchReport {
+    let mut right_order: Vec<usize> = (0..right_side.len()).collect();
+    right_order.sort_by_key(|idx| right_side[*idx].timestamp);
+
+    // кандидаты: (запись первого набора, запись второго набора, оценка)
+    let mut edges: Vec<(usize, usize, u64)> = Vec::new();
+    for (left_idx, tx) in left_side.iter().enumerate() {
+        let from_ts = tx.timestamp.saturating_sub(scoring.timestamp_window_ms);
+        let start = right_order.partition_point(|idx| right_side[*idx].timestamp < from_ts);
+        for right_idx in right_order[start..].iter().copied() {
+            if right_side[right_idx].timestamp > tx.timestamp.saturating_add(scoring.timestamp_window_ms) {
+                break;
+            }
+            if let Some(score) = scoring.score(tx, &right_side[right_idx]) {
+                edges.push((left_idx, right_idx, score));
+            }
+        }
+    }
+
+    let mut left_pair: Vec<Option<(usize, u64)>> = vec![None; left_side.len()];
+    let mut right_matched = vec![false; right_side.len()];
+    loop {
+        let left_best = best_pairs(&edges, left_side.len(), scoring.ambiguity_margin,
+            |(left_idx, right_idx, score)| (left_idx, right_idx, score));
+        let right_best = best_pairs(&edges, right_side.len(), scoring.ambiguity_margin,
+            |(left_idx, right_idx, score)| (right_idx, left_idx, score));
+
+        let mut found = false;
+        for (left_idx, best) in left_best.iter().enumerate() {
+            let Some(Best { other: right_idx, score, clear: true }) = *best else { continue };
+            if matches!(right_best[right_idx], Some(Best { other, clear: true, .. }) if other == left_idx) {
+                left_pair[left_idx] = Some((right_idx, score));
+                right_matched[right_idx] = true;
+                found = true;
+            }
+        }
+        if !found {
+            break;
+        }
+        edges.retain(|(left_idx, right_idx, _)| left_pair[*left_idx].is_none() && !right_matched[*right_idx]);
+    }
+
+    let mut report = MatchReport::default();
+    let mut candidate_of = vec![false; right_side.len()];
+    edges.sort_by(|(l1, r1, s1), (l2, r2, s2)| l1.cmp(l2).then(s2.cmp(s1)).then(r1.cmp(r2)));
+    for (left_idx, tx) in left_side.iter().enumerate() {
+        if let Some((right_idx, score)) = left_pair[left_idx] {
+            report.matched.push(FuzzyMatch { left: tx.clone(), right: right_side[right_idx].clone(), score });
+            continue;
+        }
+        let candidates: Vec<MatchCandidate> = edges
+            .iter()
+            .filter(|(edge_left, _, _)| *edge_left == left_idx)
+            .map(|(_, right_idx, score)| {
+                candidate_of[*right_idx] = true;
+                MatchCandidate { tx: right_side[*right_idx].clone(), score: *score }
+            })
+            .collect();
+        if candidates.is_empty() {
+            report.unmatched_left.push(tx.clone());
+        } else {
+            report.ambiguous.push(AmbiguousMatch { left: tx.clone(), candidates });
+        }
+    }
+    report.unmatched_right = right_side
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !right_matched[*idx] && !candidate_of[*idx])
+        .map(|(_, tx)| tx.clone())
+        .collect();
+
+    report.matched.sort_by(|m1, m2| m1.left.cmp(&m2.left));
+    report.ambiguous.sort_by(|a1, a2| a1.left.cmp(&a2.left));
+    report.unmatched_left.sort();
+    report.unmatched_right.sort();
+    report
+}
+
+/// Лучший кандидат записи, clear - остальные кандидаты хуже более чем на ambiguity_margin
+#[derive(Clone, Copy)]
+struct Best {
+    other: usize,
+    score: u64,
+    clear: bool,
+}
+
+fn best_pairs<F>(edges: &[(usize, usize, u64)], len: usize, margin: u64, orient: F) -> Vec<Option<Best>>
+where
+    F: Fn((usize, usize, u64)) -> (usize, usize, u64),
+{
+    let mut best: Vec<Option<Best>> = vec![None; len];
+    let mut second: Vec<Option<u64>> = vec![None; len];
+    for edge in edges {
+        let (idx, other, score) = orient(*edge);
+        match best[idx] {
+            Some(current) if current.score >= score => second[idx] = second[idx].max(Some(score)),
+            current => {
+                second[idx] = second[idx].max(current.map(|current| current.score));
+                best[idx] = Some(Best { other, score, clear: true });
+            }
+        }
+    }
+    for (best, second) in best.iter_mut().zip(second) {
+        if let (Some(best), Some(second)) = (best.as_mut(), second) {
+            best.clear = best.score - second > margin;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    #[test]
+    fn test_score() -> Result<(), Box<dyn std::error::Error>> {
+        let scoring = MatchScoring::from_read(&mut r#"{"amount_tolerance": 99, "min_score": 50}"#.as_bytes())?;
+        assert_eq!(scoring.timestamp_window_ms, 60_000);
+
+        let left = test_tx(1, TransactionType::TRANSFER, 10, 20, 1000).build()?;
+        let right = test_tx(900, TransactionType::TRANSFER, 10, 20, 1000);
+        assert_eq!(scoring.score(&left, &right.clone().build()?), Some(100));
+        assert_eq!(scoring.score(&left, &right.clone().to_user_id(30).amount(1050).timestamp(30_000).build()?),
+            Some(20 + 20 + 10));
+        assert_eq!(scoring.score(&left, &right.clone().amount(1100).build()?), None);
+        assert_eq!(scoring.score(&left, &right.clone().timestamp(60_001).build()?), None);
+        assert_eq!(scoring.score(&left, &right.tx_type(TransactionType::DEPOSIT).build()?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_shared_party() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![test_tx(1, TransactionType::TRANSFER, 10, 20, 500).timestamp(1000).build()?];
+        let right = vec![test_tx(901, TransactionType::TRANSFER, 30, 40, 500).timestamp(1000).build()?];
+
+        let report = fuzzy_match(&left, &right, &MatchScoring::default());
+        assert!(report.matched.is_empty());
+        assert_eq!((report.unmatched_left, report.unmatched_right), (left.clone(), right.clone()));
+
+        let any_parties = MatchScoring { min_parties: 0, ..Default::default() };
+        assert_eq!(any_parties.score(&left[0], &right[0]), Some(60));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzzy_match() -> Result<(), Box<dyn std::error::Error>> {
+        let left = vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 500).timestamp(1000).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 500).timestamp(5000).build()?,
+            test_tx(3, TransactionType::TRANSFER, 30, 40, 700).timestamp(9000).build()?,
+            test_tx(4, TransactionType::TRANSFER, 30, 40, 700).timestamp(9000).build()?,
+            test_tx(5, TransactionType::TRANSFER, 50, 60, 100).timestamp(1000).build()?,
+        ];
+        let right = vec![
+            test_tx(901, TransactionType::TRANSFER, 10, 20, 500).timestamp(5100).build()?,
+            test_tx(902, TransactionType::TRANSFER, 10, 20, 500).timestamp(1100).build()?,
+            test_tx(903, TransactionType::TRANSFER, 30, 40, 700).timestamp(9500).build()?,
+            test_tx(904, TransactionType::TRANSFER, 30, 40, 700).timestamp(9500).build()?,
+            test_tx(905, TransactionType::TRANSFER, 70, 80, 900).timestamp(1000).build()?,
+        ];
+
+        let report = fuzzy_match(&left, &right, &MatchScoring { timestamp_window_ms: 1000, ..Default::default() });
+
+        let matched: Vec<(u64, u64)> = report.matched.iter().map(|pair| (pair.left.tx_id, pair.right.tx_id)).collect();
+        assert_eq!(matched, vec![(1, 902), (2, 901)]);
+        let ambiguous: Vec<(u64, Vec<u64>)> = report.ambiguous
+            .iter()
+            .map(|amb| (amb.left.tx_id, amb.candidates.iter().map(|cand| cand.tx.tx_id).collect()))
+            .collect();
+        assert_eq!(ambiguous, vec![(3, vec![903, 904]), (4, vec![903, 904])]);
+        assert_eq!(report.unmatched_left, vec![left[4].clone()]);
+        assert_eq!(report.unmatched_right, vec![right[4].clone()]);
+        assert!(!report.is_complete());
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Transaction, TransactionStatus, TransactionType,
+    error::BuildError,
+    ledger::signed_amount,
+};
+
+/// Метка описания расчетной транзакции неттинга
+pub const SETTLEMENT_TAG: &str = "NETTING SETTLEMENT";
+
+/// Чистая двусторонняя позиция пары пользователей
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BilateralPosition {
+    /// - debtor – пользователь, перечисливший паре больше, чем получил
+    pub debtor: u64,
+    /// - creditor – пользователь, получивший от пары больше, чем перечислил
+    pub creditor: u64,
+    /// - amount – разница встречных переводов
+    pub amount: u128,
+}
+
+/// Результат неттинга переводов за период
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct NettingReport {
+    /// - bilateral – ненулевые двусторонние позиции в порядке (debtor, creditor)
+    pub bilateral: Vec<BilateralPosition>,
+    /// - positions – чистая позиция пользователя относительно системы:
+    ///   положительная - пользователь получил больше, чем перечислил
+    pub positions: BTreeMap<u64, i128>,
+}
+
+/// Функция расчета чистых позиций по успешным переводам
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции, учитываются только TRANSFER в статусе SUCCESS
+/// * from_ts: u64 - начало периода (включительно)
+/// * to_ts: u64 - окончание периода (не включительно)
+///
+/// # Возвращаемое значение
+/// NettingReport, пользователи с нулевой позицией не включаются
+pub fn net_positions(data: &[Transaction], from_ts: u64, to_ts: u64) -> NettingReport {
+    let mut pairs: BTreeMap<(u64, u64), i128> = BTreeMap::new();
+    let mut positions: BTreeMap<u64, i128> = BTreeMap::new();
+
+    let transfers = data.iter().filter(|tx| {
+        tx.tx_type == TransactionType::TRANSFER
+            && tx.status == TransactionStatus::SUCCESS
+            && tx.timestamp >= from_ts
+            && tx.timestamp < to_ts
+            && tx.from_user_id != tx.to_user_id
+    });
+
+    for tx in transfers {
+        let amount = tx.amount as i128;
+        // Позиция пары хранится для (меньший id, больший id): положительная - меньший id должен большему
+        let (key, sign) = if tx.from_user_id < tx.to_user_id {
+            ((tx.from_user_id, tx.to_user_id), 1)
+        } else {
+            ((tx.to_user_id, tx.from_user_id), -1)
+        };
+        *pairs.entry(key).or_default() += sign * amount;
+        *positions.entry(tx.from_user_id).or_default() -= amount;
+        *positions.entry(tx.to_user_id).or_default() += amount;
+    }
+
+    let mut bilateral: Vec<BilateralPosition> = pairs
+        .into_iter()
+        .filter(|(_, net)| *net != 0)
+        .map(|((low, high), net)| {
+            let (debtor, creditor) = if net > 0 { (low, high) } else { (high, low) };
+            BilateralPosition { debtor, creditor, amount: net.unsigned_abs() }
+        })
+        .collect();
+    bilateral.sort_by_key(|position| (position.debtor, position.creditor));
+    positions.retain(|_, position| *position != 0);
+
+    NettingReport { bilateral, positions }
+}
+
+/// Наибольшее число ненулевых позиций, для которого [`settle`] ищет точный минимум переводов
+pub const EXACT_SETTLEMENT_LIMIT: usize = 20;
+
+/// Функция формирования расчетных переводов, погашающих чистые позиции
+///
+/// Группа из k позиций с нулевой суммой погашается k − 1 переводами, поэтому наименьшее число
+/// переводов равно n − m, где n - число ненулевых позиций, m - наибольшее число непересекающихся
+/// групп с нулевой суммой. Пока n не превышает [`EXACT_SETTLEMENT_LIMIT`], разбиение на группы
+/// находится точно динамическим программированием по подмножествам позиций (O(2^n · n)).
+/// При большем n задача (NP-трудная) решается жадно по всем позициям сразу, и число переводов
+/// не превышает n − 1, но может быть больше минимума.
+///
+/// Внутри группы наибольший должник переводит наибольшему кредитору меньшую из их позиций.
+/// Сумма, не помещающаяся в u64, разбивается на несколько переводов.
+///
+/// # Аргументы
+///
+/// * positions - чистые позиции пользователей (см. [`NettingReport::positions`])
+/// * first_tx_id: u64 - идентификатор первого расчетного перевода, далее по возрастанию
+/// * timestamp: u64 - время расчетных переводов
+///
+/// # Возвращаемое значение
+/// Переводы TRANSFER в статусе SUCCESS с описанием [`SETTLEMENT_TAG`]
+///
+/// # Ошибки
+/// BuildError::IdOverflow - идентификатор очередного перевода не помещается в u64.
+///
+/// # Пример
+/// ```ignore
+/// let report = net_positions(&tx_vec, from_ts, to_ts);
+/// let settlements = settle(&report.positions, 1_000_000, to_ts)?;
+/// assert!(verify_settlement(&report.positions, &settlements).is_empty());
+/// ```
+pub fn settle(positions: &BTreeMap<u64, i128>, first_tx_id: u64,
+    timestamp: u64) -> Result<Vec<Transaction>, BuildError> {
+    let nonzero: Vec<(u64, i128)> = positions
+        .iter()
+        .filter(|(_, position)| **position != 0)
+        .map(|(user_id, position)| (*user_id, *position))
+        .collect();
+
+    let groups = if nonzero.len() <= EXACT_SETTLEMENT_LIMIT {
+        zero_sum_groups(&nonzero)
+    } else {
+        vec![nonzero]
+    };
+
+    let mut result: Vec<Transaction> = Vec::new();
+    for group in groups {
+        settle_greedy(&group, first_tx_id, timestamp, &mut result)?;
+    }
+    Ok(result)
+}
+
+/// Разбивает позиции на наибольшее число групп с нулевой суммой.
+///
+/// best[mask] - наибольшее число групп с нулевой суммой, на которые можно разбить префикс
+/// перестановки элементов mask; sum[mask] = 0 замыкает очередную группу. Группы восстанавливаются
+/// по цепочке масок от полной к пустой и упорядочиваются по идентификаторам пользователей.
+/// При ненулевой общей сумме остаток образует отдельную группу.
+fn zero_sum_groups(positions: &[(u64, i128)]) -> Vec<Vec<(u64, i128)>> {
+    let n = positions.len();
+    let full = (1usize << n) - 1;
+
+    let mut sum = vec![0i128; full + 1];
+    let mut best = vec![0u8; full + 1];
+    for mask in 1..=full {
+        let low = mask.trailing_zeros() as usize;
+        sum[mask] = sum[mask & (mask - 1)] + positions[low].1;
+        let prev = (0..n).filter(|i| mask & (1 << i) != 0).map(|i| best[mask ^ (1 << i)]).max().unwrap_or(0);
+        best[mask] = prev + u8::from(sum[mask] == 0);
+    }
+
+    let mut groups: Vec<Vec<(u64, i128)>> = Vec::new();
+    let mut group: Vec<(u64, i128)> = Vec::new();
+    let mut mask = full;
+    while mask != 0 {
+        if sum[mask] == 0 && !group.is_empty() {
+            groups.push(std::mem::take(&mut group));
+        }
+        let target = best[mask] - u8::from(sum[mask] == 0);
+        let i = (0..n)
+            .find(|i| mask & (1 << i) != 0 && best[mask ^ (1 << i)] == target)
+            .unwrap_or(mask.trailing_zeros() as usize);
+        group.push(positions[i]);
+        mask ^= 1 << i;
+    }
+    groups.push(group);
+    for group in groups.iter_mut() {
+        group.sort_unstable();
+    }
+    groups.sort_unstable();
+    groups
+}
+
+/// Погашает позиции переводами от наибольшего должника наибольшему кредитору
+fn settle_greedy(positions: &[(u64, i128)], first_tx_id: u64, timestamp: u64,
+    result: &mut Vec<Transaction>) -> Result<(), BuildError> {
+    let mut debtors: Vec<(u64, u128)> = Vec::new();
+    let mut creditors: Vec<(u64, u128)> = Vec::new();
+    for (user_id, position) in positions {
+        if *position < 0 {
+            debtors.push((*user_id, position.unsigned_abs()));
+        } else if *position > 0 {
+            creditors.push((*user_id, position.unsigned_abs()));
+        }
+    }
+
+    loop {
+        // Наибольшая позиция, при равенстве - меньший идентификатор пользователя
+        let largest = |list: &[(u64, u128)]| {
+            list.iter().enumerate().max_by_key(|(_, (user_id, rest))| (*rest, std::cmp::Reverse(*user_id))).map(|(i, _)| i)
+        };
+        let (Some(d), Some(c)) = (largest(&debtors), largest(&creditors)) else {
+            break;
+        };
+        let amount = debtors[d].1.min(creditors[c].1);
+        let mut rest = amount;
+        while rest > 0 {
+            let part = rest.min(u64::MAX as u128);
+            let tx_id = u64::try_from(result.len())
+                .ok()
+                .and_then(|offset| first_tx_id.checked_add(offset))
+                .ok_or(BuildError::IdOverflow)?;
+            result.push(Transaction::builder()
+                .tx_id(tx_id)
+                .tx_type(TransactionType::TRANSFER)
+                .from_user_id(debtors[d].0)
+                .to_user_id(creditors[c].0)
+                .amount(part as u64)
+                .timestamp(timestamp)
+                .status(TransactionStatus::SUCCESS)
+                .description(SETTLEMENT_TAG)
+                .build()?);
+            rest -= part;
+        }
+        debtors[d].1 -= amount;
+        creditors[c].1 -= amount;
+        debtors.retain(|(_, rest)| *rest > 0);
+        creditors.retain(|(_, rest)| *rest > 0);
+    }
+    Ok(())
+}
+
+/// Расхождение позиции пользователя и результата расчетных переводов
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PositionMismatch {
+    /// - user_id – идентификатор пользователя
+    pub user_id: u64,
+    /// - position – чистая позиция пользователя
+    pub position: i128,
+    /// - settled – сумма расчетных переводов пользователю за вычетом переводов от него
+    pub settled: i128,
+}
+
+/// Функция проверки сохранения позиций расчетными переводами
+///
+/// Для каждого пользователя сумма расчетных переводов (зачисления минус списания) должна
+/// совпадать с его чистой позицией: тогда исходные переводы можно заменить расчетными,
+/// и итоговые балансы всех пользователей не изменятся.
+///
+/// # Возвращаемое значение
+/// Список расхождений, пустой список - позиции сохранены
+pub fn verify_settlement(positions: &BTreeMap<u64, i128>, settlements: &[Transaction]) -> Vec<PositionMismatch> {
+    let mut settled: BTreeMap<u64, i128> = BTreeMap::new();
+    for tx in settlements {
+        for user_id in tx.participants() {
+            *settled.entry(user_id).or_default() += signed_amount(tx, user_id);
+        }
+    }
+
+    let mut users: Vec<u64> = positions.keys().chain(settled.keys()).copied().collect();
+    users.sort_unstable();
+    users.dedup();
+
+    users
+        .into_iter()
+        .map(|user_id| PositionMismatch {
+            user_id,
+            position: positions.get(&user_id).copied().unwrap_or(0),
+            settled: settled.get(&user_id).copied().unwrap_or(0),
+        })
+        .filter(|mismatch| mismatch.position != mismatch.settled)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tx;
+
+    #[test]
+    fn test_net_positions() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?,
+            test_tx(2, TransactionType::TRANSFER, 20, 10, 30).timestamp(10).build()?,
+            test_tx(3, TransactionType::TRANSFER, 20, 30, 70).timestamp(20).build()?,
+            test_tx(4, TransactionType::TRANSFER, 30, 10, 5).timestamp(30).build()?,
+            test_tx(5, TransactionType::TRANSFER, 10, 30, 1000).timestamp(40).status(TransactionStatus::FAILURE).build()?,
+            test_tx(6, TransactionType::TRANSFER, 10, 30, 1000).timestamp(500).build()?,
+        ];
+        let report = net_positions(&data, 0, 100);
+        assert_eq!(report.bilateral, vec![
+            BilateralPosition { debtor: 10, creditor: 20, amount: 70 },
+            BilateralPosition { debtor: 20, creditor: 30, amount: 70 },
+            BilateralPosition { debtor: 30, creditor: 10, amount: 5 },
+        ]);
+        assert_eq!(report.positions, BTreeMap::from([(10, -65), (30, 65)]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_settle() -> Result<(), BuildError> {
+        let positions = BTreeMap::from([(10, -50), (20, -30), (30, 60), (40, 20)]);
+        let settlements = settle(&positions, 100, 1000)?;
+        let legs: Vec<(u64, u64, u64, u64)> = settlements
+            .iter()
+            .map(|tx| (tx.tx_id, tx.from_user_id, tx.to_user_id, tx.amount))
+            .collect();
+        assert_eq!(legs, vec![(100, 10, 30, 50), (101, 20, 40, 20), (102, 20, 30, 10)]);
+        assert!(verify_settlement(&positions, &settlements).is_empty());
+
+        let broken = &settlements[..2];
+        assert_eq!(verify_settlement(&positions, broken), vec![
+            PositionMismatch { user_id: 20, position: -30, settled: -20 },
+            PositionMismatch { user_id: 30, position: 60, settled: 50 },
+        ]);
+
+        assert_eq!(settle(&positions, u64::MAX - 1, 1000), Err(BuildError::IdOverflow));
+        assert_eq!(settle(&BTreeMap::from([(10, -50), (30, 50)]), u64::MAX, 1000)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_settle_minimal() -> Result<(), BuildError> {
+        // Группы {1, 5, 6} и {2, 3, 4}: минимум 2 + 2 перевода, жадный расчет по всем позициям - 5
+        let positions = BTreeMap::from([(1, -4), (2, -3), (3, -3), (4, 6), (5, 2), (6, 2)]);
+        let mut greedy: Vec<Transaction> = Vec::new();
+        settle_greedy(&positions.clone().into_iter().collect::<Vec<(u64, i128)>>(), 1, 0, &mut greedy)?;
+        assert_eq!(greedy.len(), 5);
+
+        let settlements = settle(&positions, 1, 0)?;
+        let legs: Vec<(u64, u64, u64)> = settlements
+            .iter()
+            .map(|tx| (tx.from_user_id, tx.to_user_id, tx.amount))
+            .collect();
+        assert_eq!(legs, vec![(1, 5, 2), (1, 6, 2), (2, 4, 3), (3, 4, 3)]);
+        assert!(verify_settlement(&positions, &settlements).is_empty());
+        Ok(())
+    }
+}
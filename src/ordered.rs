@@ -0,0 +1,388 @@
+use serde::Serialize;
+use strum_macros::Display;
+
+use crate::{FieldChange, Transaction, diff::CompareOptions, error::ParserError};
+
+/// Виды правок
+#[derive(Display, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EditKind {
+    /// - INSERT – запись есть только во втором наборе
+    #[strum(serialize = "INSERT")]
+    #[serde(rename = "INSERT")]
+    Insert,
+    /// - DELETE – запись есть только в первом наборе
+    #[strum(serialize = "DELETE")]
+    #[serde(rename = "DELETE")]
+    Delete,
+    /// - SUBSTITUTE – запись первого набора заменена записью второго
+    #[strum(serialize = "SUBSTITUTE")]
+    #[serde(rename = "SUBSTITUTE")]
+    Substitute,
+}
+
+/// Правка, переводящая первый набор во второй
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Edit {
+    /// - kind – вид правки
+    pub kind: EditKind,
+    /// - left_pos – позиция в первом наборе (для INSERT - позиция, перед которой вставляется запись)
+    pub left_pos: usize,
+    /// - right_pos – позиция во втором наборе (для DELETE - позиция, перед которой удалена запись)
+    pub right_pos: usize,
+    /// - left – запись первого набора (DELETE, SUBSTITUTE)
+    pub left: Option<Transaction>,
+    /// - right – запись второго набора (INSERT, SUBSTITUTE)
+    pub right: Option<Transaction>,
+    /// - changes – различающиеся поля (SUBSTITUTE)
+    pub changes: Vec<FieldChange>,
+}
+
+/// Результат упорядоченного сравнения наборов
+#[derive(Serialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct OrderedDiff {
+    /// - edits – минимальный сценарий правок в порядке следования записей
+    pub edits: Vec<Edit>,
+}
+
+impl OrderedDiff {
+    /// Возвращает true, если последовательности совпадают
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Возвращает первую правку - место первого расхождения последовательностей
+    pub fn first_divergence(&self) -> Option<&Edit> {
+        self.edits.first()
+    }
+}
+
+/// Функция упорядоченного сравнения наборов транзакций
+///
+/// Наборы сравниваются как последовательности: строится минимальный по числу вставок и удалений
+/// сценарий правок (алгоритм Майерса), затем соседние удаления и вставки объединяются в замены.
+/// Совпадающие начало и конец последовательностей отбрасываются заранее, время работы
+/// O((N + M) · D), где D - число вставок и удалений, дополнительная память O(N + M),
+/// поэтому большие наборы с небольшим числом расхождений сравниваются быстро.
+///
+/// # Аргументы
+///
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+/// * options: &CompareOptions - правила сравнения записей (ключевые поля не используются)
+///
+/// # Возвращаемое значение
+/// OrderedDiff, пустой - последовательности совпадают
+///
+/// # Пример
+/// ```ignore
+/// let diff = ordered_diff(&original_vec, &replayed_vec, &CompareOptions::default());
+/// if let Some(edit) = diff.first_divergence() {
+///     println!("First divergence at record {}", edit.left_pos);
+/// }
+/// ```
+pub fn ordered_diff(left_side: &[Transaction], right_side: &[Transaction], options: &CompareOptions) -> OrderedDiff {
+    let eq = |x: usize, y: usize| options.is_match(&left_side[x], &right_side[y]);
+
+    let prefix = (0..left_side.len().min(right_side.len())).take_while(|i| eq(*i, *i)).count();
+    let suffix = (0..(left_side.len() - prefix).min(right_side.len() - prefix))
+        .take_while(|i| eq(left_side.len() - 1 - i, right_side.len() - 1 - i))
+        .count();
+
+    let raw = myers(left_side.len() - prefix - suffix, right_side.len() - prefix - suffix,
+        |x, y| eq(prefix + x, prefix + y));
+
+    // Соседние (без совпадающих записей между ними) удаления и вставки объединяются в замены
+    let mut edits: Vec<Edit> = Vec::new();
+    let mut run_start = 0;
+    while run_start < raw.len() {
+        let mut run_end = run_start + 1;
+        while run_end < raw.len() && raw[run_end].at() == raw[run_end - 1].after() {
+            run_end += 1;
+        }
+        let run = &raw[run_start..run_end];
+        let deletes: Vec<(usize, usize)> = run.iter().filter_map(|op| op.deleted()).collect();
+        let inserts: Vec<(usize, usize)> = run.iter().filter_map(|op| op.inserted()).collect();
+        let (start_x, start_y) = run[0].at();
+
+        for (i, (x, _)) in deletes.iter().enumerate() {
+            let left = &left_side[prefix + x];
+            match inserts.get(i) {
+                Some((_, y)) => {
+                    let right = &right_side[prefix + y];
+                    edits.push(Edit { kind: EditKind::Substitute, left_pos: prefix + x, right_pos: prefix + y,
+                        left: Some(left.clone()), right: Some(right.clone()),
+                        changes: options.field_changes(left, right) });
+                }
+                None => edits.push(Edit { kind: EditKind::Delete, left_pos: prefix + x,
+                    right_pos: prefix + start_y + inserts.len(), left: Some(left.clone()), right: None,
+                    changes: Vec::new() }),
+            }
+        }
+        for (_, y) in inserts.iter().skip(deletes.len()) {
+            edits.push(Edit { kind: EditKind::Insert, left_pos: prefix + start_x + deletes.len(),
+                right_pos: prefix + y, left: None, right: Some(right_side[prefix + y].clone()),
+                changes: Vec::new() });
+        }
+        run_start = run_end;
+    }
+
+    OrderedDiff { edits }
+}
+
+/// Элементарная правка: удаление a[x] или вставка b[y] в точке (x, y)
+#[derive(Debug, Clone, Copy)]
+enum RawOp {
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+impl RawOp {
+    fn at(&self) -> (usize, usize) {
+        match *self {
+            Self::Delete(x, y) | Self::Insert(x, y) => (x, y),
+        }
+    }
+
+    fn after(&self) -> (usize, usize) {
+        match *self {
+            Self::Delete(x, y) => (x + 1, y),
+            Self::Insert(x, y) => (x, y + 1),
+        }
+    }
+
+    fn deleted(&self) -> Option<(usize, usize)> {
+        match *self {
+            Self::Delete(x, y) => Some((x, y)),
+            Self::Insert(..) => None,
+        }
+    }
+
+    fn inserted(&self) -> Option<(usize, usize)> {
+        match *self {
+            Self::Insert(x, y) => Some((x, y)),
+            Self::Delete(..) => None,
+        }
+    }
+}
+
+/// Алгоритм Майерса в линейной памяти: минимальный сценарий удалений и вставок для
+/// последовательностей длины n и m. Последовательности делятся средней змейкой (snake)
+/// пополам по числу правок, половины обрабатываются рекурсивно.
+fn myers(n: usize, m: usize, eq: impl Fn(usize, usize) -> bool) -> Vec<RawOp> {
+    let mut result: Vec<RawOp> = Vec::new();
+    myers_range(&eq, (0, n), (0, m), &mut result);
+    result
+}
+
+/// Добавляет в result сценарий правок для a[x0..x1] и b[y0..y1]
+fn myers_range(eq: &impl Fn(usize, usize) -> bool, (mut x0, mut x1): (usize, usize), (mut y0, mut y1): (usize, usize),
+    result: &mut Vec<RawOp>) {
+
+    while x0 < x1 && y0 < y1 && eq(x0, y0) {
+        x0 += 1;
+        y0 += 1;
+    }
+    while x0 < x1 && y0 < y1 && eq(x1 - 1, y1 - 1) {
+        x1 -= 1;
+        y1 -= 1;
+    }
+
+    if x0 == x1 || y0 == y1 {
+        result.extend((x0..x1).map(|x| RawOp::Delete(x, y0)));
+        result.extend((y0..y1).map(|y| RawOp::Insert(x1, y)));
+        return;
+    }
+
+    match middle_snake(eq, (x0, x1), (y0, y1)) {
+        Some((x, y)) => {
+            myers_range(eq, (x0, x), (y0, y), result);
+            myers_range(eq, (x, x1), (y, y1), result);
+        }
+        None => {
+            result.extend((x0..x1).map(|x| RawOp::Delete(x, y0)));
+            result.extend((y0..y1).map(|y| RawOp::Insert(x1, y)));
+        }
+    }
+}
+
+/// Ищет точку (x, y) на минимальном пути правок, делящую число правок пополам.
+/// Пути строятся одновременно от начала (forward) и от конца (backward) последовательностей,
+/// для каждой диагонали k = x - y хранится наибольшее достигнутое смещение (-1 - не достигнута).
+fn middle_snake(eq: &impl Fn(usize, usize) -> bool, (x0, x1): (usize, usize),
+    (y0, y1): (usize, usize)) -> Option<(usize, usize)> {
+
+    let (n, m) = ((x1 - x0) as isize, (y1 - y0) as isize);
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d + 1;
+    let len = 2 * max_d + 3;
+    let mut forward: Vec<isize> = vec![-1; len as usize];
+    let mut backward: Vec<isize> = vec![-1; len as usize];
+    forward[(offset + 1) as usize] = 0;
+    backward[(offset + 1) as usize] = 0;
+
+    let delta = n - m;
+    // При нечетной разнице длин пути встречаются на шаге прямого поиска, иначе - обратного
+    let front = delta % 2 != 0;
+    // Диагонали, вышедшие за границы последовательностей, далее не рассматриваются
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..=max_d {
+        for k1 in (-d + k1_start..=d - k1_end).step_by(2) {
+            let idx = (offset + k1) as usize;
+            let mut x = if k1 == -d || (k1 != d && forward[idx - 1] < forward[idx + 1]) {
+                forward[idx + 1]
+            } else {
+                forward[idx - 1] + 1
+            };
+            let mut y = x - k1;
+            while x < n && y < m && eq(x0 + x as usize, y0 + y as usize) {
+                x += 1;
+                y += 1;
+            }
+            forward[idx] = x;
+            if x > n {
+                k1_end += 2;
+            } else if y > m {
+                k1_start += 2;
+            } else if front {
+                let k2 = offset + delta - k1;
+                if (0..len).contains(&k2) && backward[k2 as usize] != -1 && x >= n - backward[k2 as usize] {
+                    return Some((x0 + x as usize, y0 + y as usize));
+                }
+            }
+        }
+
+        for k2 in (-d + k2_start..=d - k2_end).step_by(2) {
+            let idx = (offset + k2) as usize;
+            let mut x = if k2 == -d || (k2 != d && backward[idx - 1] < backward[idx + 1]) {
+                backward[idx + 1]
+            } else {
+                backward[idx - 1] + 1
+            };
+            let mut y = x - k2;
+            while x < n && y < m && eq(x1 - 1 - x as usize, y1 - 1 - y as usize) {
+                x += 1;
+                y += 1;
+            }
+            backward[idx] = x;
+            if x > n {
+                k2_end += 2;
+            } else if y > m {
+                k2_start += 2;
+            } else if !front {
+                let k1 = offset + delta - k2;
+                if (0..len).contains(&k1) && forward[k1 as usize] != -1 {
+                    let forward_x = forward[k1 as usize];
+                    let forward_y = forward_x - (delta - k2);
+                    if forward_x >= n - x {
+                        return Some((x0 + forward_x as usize, y0 + forward_y as usize));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Функция записи результата упорядоченного сравнения в формате JSON
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер, stdout)
+/// * diff - результат упорядоченного сравнения
+pub fn write_ordered_json<W: std::io::Write>(target: &mut W, diff: &OrderedDiff) -> Result<(), ParserError> {
+    #[derive(Serialize)]
+    struct OrderedDocument<'a> {
+        equal: bool,
+        first_divergence: Option<(usize, usize)>,
+        edits: &'a [Edit],
+    }
+
+    let document = OrderedDocument {
+        equal: diff.is_empty(),
+        first_divergence: diff.first_divergence().map(|edit| (edit.left_pos, edit.right_pos)),
+        edits: &diff.edits,
+    };
+    serde_json::to_writer_pretty(&mut *target, &document)?;
+    target.write_all(b"\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, error::BuildError, test_tx};
+
+    fn script(diff: &OrderedDiff) -> Vec<(EditKind, usize, usize)> {
+        diff.edits.iter().map(|edit| (edit.kind, edit.left_pos, edit.right_pos)).collect()
+    }
+
+    #[test]
+    fn test_ordered_diff() -> Result<(), Box<dyn std::error::Error>> {
+        let options = CompareOptions::default();
+        let deposits = |ids: &[u64]| -> Result<Vec<Transaction>, BuildError> {
+            ids.iter().map(|&tx_id| test_tx(tx_id, TransactionType::DEPOSIT, 0, 10, 1).timestamp(tx_id).build()).collect()
+        };
+        let left = deposits(&[1, 2, 3, 4, 5, 6])?;
+        let mut right = deposits(&[1, 3, 2, 4, 5, 7, 6])?;
+        right[3].amount = 9;
+
+        let diff = ordered_diff(&left, &right, &options);
+        assert_eq!(script(&diff), vec![
+            (EditKind::Insert, 1, 1),
+            (EditKind::Substitute, 2, 3),
+            (EditKind::Delete, 3, 4),
+            (EditKind::Insert, 5, 5),
+        ]);
+        assert_eq!(diff.edits[1].changes.len(), 3);
+        assert_eq!(diff.first_divergence().map(|edit| edit.left_pos), Some(1));
+
+        assert!(ordered_diff(&left, &left, &options).is_empty());
+        assert_eq!(script(&ordered_diff(&[], &left[..2], &options)),
+            vec![(EditKind::Insert, 0, 0), (EditKind::Insert, 0, 1)]);
+        assert_eq!(script(&ordered_diff(&left[..2], &[], &options)),
+            vec![(EditKind::Delete, 0, 0), (EditKind::Delete, 1, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_diff_is_minimal() -> Result<(), Box<dyn std::error::Error>> {
+        let options = CompareOptions::default();
+        // Перестановка блоков: минимум - удалить и вставить по одной записи на каждый сдвиг
+        let left = (0..200)
+            .map(|tx_id| test_tx(tx_id, TransactionType::DEPOSIT, 0, 10, 1).timestamp(tx_id).build())
+            .collect::<Result<Vec<Transaction>, BuildError>>()?;
+        let mut right: Vec<Transaction> = left.clone();
+        right.swap(10, 150);
+        right.remove(100);
+        right.insert(0, test_tx(1000, TransactionType::DEPOSIT, 0, 10, 1).timestamp(1000).build()?);
+
+        let diff = ordered_diff(&left, &right, &options);
+        let mut applied: Vec<Transaction> = Vec::new();
+        let mut pos = 0;
+        for edit in &diff.edits {
+            applied.extend(left[pos..edit.left_pos].iter().cloned());
+            pos = edit.left_pos;
+            match edit.kind {
+                EditKind::Insert => applied.extend(edit.right.clone()),
+                EditKind::Delete => pos += 1,
+                EditKind::Substitute => {
+                    applied.extend(edit.right.clone());
+                    pos += 1;
+                }
+            }
+        }
+        applied.extend(left[pos..].iter().cloned());
+        assert_eq!(applied, right);
+        assert_eq!(diff.edits.len(), 4);
+
+        // Наборы без общих записей: все записи заменены
+        let other = (0..3000)
+            .map(|tx_id| test_tx(tx_id, TransactionType::WITHDRAWAL, 10, 0, 1).build())
+            .collect::<Result<Vec<Transaction>, BuildError>>()?;
+        let diff = ordered_diff(&left, &other[..3000], &options);
+        assert_eq!(diff.edits.iter().filter(|edit| edit.kind == EditKind::Substitute).count(), 200);
+        assert_eq!(diff.edits.len(), 3000);
+        Ok(())
+    }
+}
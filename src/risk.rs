@@ -0,0 +1,252 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::{Transaction, TransactionStatus, TransactionType, error::ParserError};
+
+/// Правило частоты переводов: более max_transfers переводов одного отправителя за окно window_ms
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct VelocityRule {
+    /// - max_transfers – допустимое число переводов в окне
+    pub max_transfers: usize,
+    /// - window_ms – окно, мс
+    pub window_ms: u64,
+}
+
+/// Правило отклонения от истории: сумма больше factor средних сумм пользователя
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct OutlierRule {
+    /// - factor – допустимая кратность средней суммы
+    pub factor: u64,
+    /// - min_history – минимальное число предыдущих транзакций пользователя для проверки
+    pub min_history: usize,
+}
+
+/// Правило встречных переводов: перевод A→B и перевод B→A в пределах окна window_ms
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RoundTripRule {
+    /// - window_ms – окно, мс
+    pub window_ms: u64,
+}
+
+/// Правило серии отказов: более max_failures транзакций пользователя в статусе FAILURE за окно window_ms
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FailureBurstRule {
+    /// - max_failures – допустимое число отказов в окне
+    pub max_failures: usize,
+    /// - window_ms – окно, мс
+    pub window_ms: u64,
+}
+
+/// Набор правил проверки, отсутствующее правило не применяется
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+#[serde(default)]
+pub struct RiskRules {
+    /// - velocity – правило частоты переводов
+    pub velocity: Option<VelocityRule>,
+    /// - amount_threshold – порог суммы транзакции
+    pub amount_threshold: Option<u64>,
+    /// - outlier – правило отклонения от истории пользователя
+    pub outlier: Option<OutlierRule>,
+    /// - round_trip – правило встречных переводов
+    pub round_trip: Option<RoundTripRule>,
+    /// - failure_burst – правило серии отказов
+    pub failure_burst: Option<FailureBurstRule>,
+}
+
+impl RiskRules {
+    /// Функция чтения правил в формате JSON
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    pub fn from_read<R: std::io::Read>(source: &mut R) -> Result<Self, ParserError> {
+        Ok(serde_json::from_reader(source)?)
+    }
+}
+
+/// Виды предупреждений
+#[derive(Display, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum AlertKind {
+    /// - VELOCITY – превышена частота переводов
+    #[strum(serialize = "VELOCITY")]
+    Velocity,
+    /// - LARGE_AMOUNT – сумма выше порога
+    #[strum(serialize = "LARGE_AMOUNT")]
+    LargeAmount,
+    /// - OUTLIER – сумма значительно выше обычной для пользователя
+    #[strum(serialize = "OUTLIER")]
+    Outlier,
+    /// - ROUND_TRIP – встречные переводы
+    #[strum(serialize = "ROUND_TRIP")]
+    RoundTrip,
+    /// - FAILURE_BURST – серия отказов
+    #[strum(serialize = "FAILURE_BURST")]
+    FailureBurst,
+}
+
+/// Предупреждение о подозрительной активности
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Alert {
+    /// - kind – вид предупреждения
+    pub kind: AlertKind,
+    /// - user_id – пользователь, инициировавший транзакции
+    pub user_id: u64,
+    /// - tx_ids – идентификаторы транзакций, вызвавших предупреждение
+    pub tx_ids: Vec<u64>,
+}
+
+/// Функция проверки транзакций по правилам
+///
+/// Транзакции рассматриваются в порядке времени. Инициатором транзакции считается получатель
+/// для DEPOSIT и отправитель для остальных типов. После срабатывания правила частоты или серии
+/// отказов окно пользователя очищается, чтобы одна серия давала одно предупреждение.
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции
+/// * rules: &RiskRules - правила проверки
+///
+/// # Возвращаемое значение
+/// Предупреждения в порядке срабатывания
+///
+/// # Пример
+/// ```ignore
+/// let rules = RiskRules { amount_threshold: Some(1_000_000), ..Default::default() };
+/// for alert in scan(&tx_vec, &rules) {
+///     println!("{} user {}: {:?}", alert.kind, alert.user_id, alert.tx_ids);
+/// }
+/// ```
+pub fn scan(data: &[Transaction], rules: &RiskRules) -> Vec<Alert> {
+    let mut txs: Vec<&Transaction> = data.iter().collect();
+    txs.sort_by_key(|tx| tx.timestamp);
+
+    let mut result: Vec<Alert> = Vec::new();
+    let mut transfers: HashMap<u64, VecDeque<&Transaction>> = HashMap::new();
+    let mut failures: HashMap<u64, VecDeque<&Transaction>> = HashMap::new();
+    let mut history: HashMap<u64, (u128, u64)> = HashMap::new();
+    let mut pairs: HashMap<(u64, u64), VecDeque<&Transaction>> = HashMap::new();
+
+    for tx in txs {
+        let user_id = match tx.tx_type {
+            TransactionType::DEPOSIT => tx.to_user_id,
+            _ => tx.from_user_id,
+        };
+        let alert = |kind: AlertKind, tx_ids: Vec<u64>| Alert { kind, user_id, tx_ids };
+
+        if let Some(rule) = &rules.velocity
+            && tx.tx_type == TransactionType::TRANSFER
+        {
+            let window = transfers.entry(user_id).or_default();
+            if let Some(tx_ids) = slide(window, tx, rule.window_ms, rule.max_transfers) {
+                result.push(alert(AlertKind::Velocity, tx_ids));
+            }
+        }
+
+        if let Some(threshold) = rules.amount_threshold
+            && tx.amount > threshold
+        {
+            result.push(alert(AlertKind::LargeAmount, vec![tx.tx_id]));
+        }
+
+        if let Some(rule) = &rules.outlier {
+            let (sum, count) = history.entry(user_id).or_default();
+            if *count as usize >= rule.min_history && tx.amount as u128 * *count as u128 > *sum * rule.factor as u128 {
+                result.push(alert(AlertKind::Outlier, vec![tx.tx_id]));
+            }
+            *sum += tx.amount as u128;
+            *count += 1;
+        }
+
+        if let Some(rule) = &rules.round_trip
+            && tx.tx_type == TransactionType::TRANSFER
+            && tx.status == TransactionStatus::SUCCESS
+        {
+            if let Some(back) = pairs.get(&(tx.to_user_id, tx.from_user_id))
+                && let Some(first) = back.iter().rev().find(|first| tx.timestamp - first.timestamp <= rule.window_ms)
+            {
+                result.push(Alert {
+                    kind: AlertKind::RoundTrip,
+                    user_id: first.from_user_id,
+                    tx_ids: vec![first.tx_id, tx.tx_id],
+                });
+            }
+            let forward = pairs.entry((tx.from_user_id, tx.to_user_id)).or_default();
+            forward.push_back(tx);
+            while forward.front().is_some_and(|first| tx.timestamp - first.timestamp > rule.window_ms) {
+                forward.pop_front();
+            }
+        }
+
+        if let Some(rule) = &rules.failure_burst
+            && tx.status == TransactionStatus::FAILURE
+        {
+            let window = failures.entry(user_id).or_default();
+            if let Some(tx_ids) = slide(window, tx, rule.window_ms, rule.max_failures) {
+                result.push(alert(AlertKind::FailureBurst, tx_ids));
+            }
+        }
+    }
+    result
+}
+
+/// Добавляет транзакцию в окно пользователя и удаляет устаревшие.
+/// Возвращает идентификаторы транзакций окна и очищает его, если их число превысило max.
+fn slide<'a>(window: &mut VecDeque<&'a Transaction>, tx: &'a Transaction, window_ms: u64, max: usize) -> Option<Vec<u64>> {
+    window.push_back(tx);
+    while window.front().is_some_and(|first| tx.timestamp - first.timestamp > window_ms) {
+        window.pop_front();
+    }
+    if window.len() > max {
+        let tx_ids = window.iter().map(|tx| tx.tx_id).collect();
+        window.clear();
+        return Some(tx_ids);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tx;
+
+    #[test]
+    fn test_velocity_and_amount() -> Result<(), Box<dyn std::error::Error>> {
+        let json = r#"{"velocity": {"max_transfers": 2, "window_ms": 1000}, "amount_threshold": 500}"#;
+        let rules = RiskRules::from_read(&mut json.as_bytes())?;
+        let data = vec![
+            test_tx(1, TransactionType::TRANSFER, 10, 20, 100).build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 30, 100).timestamp(400).build()?,
+            test_tx(3, TransactionType::TRANSFER, 10, 40, 900).timestamp(800).status(TransactionStatus::PENDING).build()?,
+            test_tx(4, TransactionType::TRANSFER, 10, 20, 100).timestamp(5000).build()?,
+        ];
+        assert_eq!(scan(&data, &rules), vec![
+            Alert { kind: AlertKind::Velocity, user_id: 10, tx_ids: vec![1, 2, 3] },
+            Alert { kind: AlertKind::LargeAmount, user_id: 10, tx_ids: vec![3] },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_outlier_round_trip_failures() -> Result<(), Box<dyn std::error::Error>> {
+        let rules = RiskRules {
+            outlier: Some(OutlierRule { factor: 5, min_history: 2 }),
+            round_trip: Some(RoundTripRule { window_ms: 1000 }),
+            failure_burst: Some(FailureBurstRule { max_failures: 1, window_ms: 1000 }),
+            ..Default::default()
+        };
+        let data = vec![
+            test_tx(1, TransactionType::WITHDRAWAL, 10, 0, 100).build()?,
+            test_tx(2, TransactionType::WITHDRAWAL, 10, 0, 100).timestamp(10).build()?,
+            test_tx(3, TransactionType::TRANSFER, 10, 20, 600).timestamp(20).build()?,
+            test_tx(4, TransactionType::TRANSFER, 20, 10, 600).timestamp(900).build()?,
+            test_tx(5, TransactionType::WITHDRAWAL, 30, 0, 10).timestamp(1000).status(TransactionStatus::FAILURE).build()?,
+            test_tx(6, TransactionType::WITHDRAWAL, 30, 0, 10).timestamp(1500).status(TransactionStatus::FAILURE).build()?,
+            test_tx(7, TransactionType::TRANSFER, 10, 20, 50).timestamp(5000).build()?,
+        ];
+        assert_eq!(scan(&data, &rules), vec![
+            Alert { kind: AlertKind::Outlier, user_id: 10, tx_ids: vec![3] },
+            Alert { kind: AlertKind::RoundTrip, user_id: 10, tx_ids: vec![3, 4] },
+            Alert { kind: AlertKind::FailureBurst, user_id: 30, tx_ids: vec![5, 6] },
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    SNAPSHOT_HEADER, Transaction, error::ParserError, ledger::{Ledger, LedgerReport}, parse_u64_value, split_csv_line};
+
+/// Запись о балансе пользователя на момент времени
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BalanceRecord {
+    /// - user_id – идентификатор пользователя
+    pub user_id: u64,
+    /// - balance – баланс в минорных единицах
+    pub balance: i64,
+    /// - as_of – Unix epoch timestamp в миллисекундах, на который зафиксирован баланс
+    pub as_of: u64,
+}
+
+/// Парсер файла балансов (формат CSV: USER_ID,BALANCE,AS_OF)
+#[derive(Default)]
+pub struct SnapshotParser {
+
+}
+
+impl SnapshotParser {
+    /// Функция чтения балансов из источника
+    /// * source - источник данных, реализующий трейт Read (файл, буфер)
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_read<R: std::io::Read>(&self, source: &mut R) -> Result<Vec<BalanceRecord>, ParserError> {
+        let mut result: Vec<BalanceRecord> = Vec::new();
+
+        let mut str_records = String::new();
+        source.read_to_string(&mut str_records)?;
+
+        let str_arr: Vec<&str> = str_records
+            .split("\n")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .filter(|s| !s.starts_with("USER_ID"))
+            .collect();
+
+        for (numb, stx) in str_arr.iter().enumerate() {
+            let tmp_vec: Vec<&str> = split_csv_line(stx);
+
+            if tmp_vec.len() != 3 {
+                return Err(ParserError::InvalidCSVStructure(numb));
+            }
+
+            result.push(BalanceRecord {
+                user_id: parse_u64_value(tmp_vec[0], numb)?,
+                balance: tmp_vec[1].parse::<i64>().map_err(|_| ParserError::InvalidValue { tx_numb: numb })?,
+                as_of: parse_u64_value(tmp_vec[2], numb)?,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Функция записи балансов в источник
+    /// * target - получатель данных реализуйщий трейт Write (файл, буфер),
+    /// * data - записи о балансах
+    pub fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[BalanceRecord]) -> Result<(), ParserError> {
+        let mut result_str = String::from(SNAPSHOT_HEADER);
+
+        for record in data {
+            result_str += format!("{},{},{}\n", record.user_id, record.balance, record.as_of).as_str();
+        }
+        target.write_all(result_str.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Функция формирования записей о балансах по балансам книги
+///
+/// # Аргументы
+///
+/// * balances - балансы пользователей
+/// * as_of - момент времени, на который зафиксированы балансы
+pub fn snapshot_of(balances: &BTreeMap<u64, i64>, as_of: u64) -> Vec<BalanceRecord> {
+    balances
+        .iter()
+        .map(|(user_id, balance)| BalanceRecord { user_id: *user_id, balance: *balance, as_of })
+        .collect()
+}
+
+/// Расхождение расчетного и заявленного баланса пользователя
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BalanceMismatch {
+    /// - user_id – идентификатор пользователя
+    pub user_id: u64,
+    /// - computed – баланс, рассчитанный по начальному снимку и транзакциям
+    pub computed: i64,
+    /// - reported – баланс из конечного снимка
+    pub reported: i64,
+}
+
+/// Результат сверки балансов
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ReconcileReport {
+    /// - mismatches – счета, расчетный и заявленный балансы которых расходятся
+    pub mismatches: Vec<BalanceMismatch>,
+    /// - ledger – результат применения транзакций к начальному снимку
+    pub ledger: LedgerReport,
+}
+
+/// Функция сверки балансов
+///
+/// # Аргументы
+///
+/// * opening: &[BalanceRecord] - начальный снимок балансов
+/// * data: &[Transaction] - транзакции, применяемые к начальному снимку
+/// * closing: &[BalanceRecord] - конечный снимок балансов
+///
+/// # Возвращаемое значение
+/// ReconcileReport со списком расхождений, упорядоченным по user_id.
+/// Счет, отсутствующий в одном из снимков, считается имеющим в нем нулевой баланс.
+/// При повторе user_id в снимке используется последняя запись.
+///
+/// # Пример
+/// ```ignore
+/// let report = reconcile(&opening, &tx_vec, &closing);
+/// for mismatch in report.mismatches {
+///     println!("{}: {} != {}", mismatch.user_id, mismatch.computed, mismatch.reported);
+/// }
+/// ```
+pub fn reconcile(opening: &[BalanceRecord], data: &[Transaction], closing: &[BalanceRecord]) -> ReconcileReport {
+    let ledger = Ledger::with_balances(to_balances(opening)).apply_all(data);
+    let reported = to_balances(closing);
+
+    let mut users: Vec<u64> = ledger.balances.keys().chain(reported.keys()).copied().collect();
+    users.sort_unstable();
+    users.dedup();
+
+    let mismatches = users
+        .into_iter()
+        .map(|user_id| BalanceMismatch {
+            user_id,
+            computed: ledger.balances.get(&user_id).copied().unwrap_or(0),
+            reported: reported.get(&user_id).copied().unwrap_or(0),
+        })
+        .filter(|mismatch| mismatch.computed != mismatch.reported)
+        .collect();
+
+    ReconcileReport { mismatches, ledger }
+}
+
+fn to_balances(records: &[BalanceRecord]) -> BTreeMap<u64, i64> {
+    records.iter().map(|record| (record.user_id, record.balance)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    fn record(user_id: u64, balance: i64, as_of: u64) -> BalanceRecord {
+        BalanceRecord { user_id, balance, as_of }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() -> Result<(), ParserError> {
+        let data = vec![record(10, 300, 1633036860000), record(20, -5, 1633036860000)];
+        let parser = SnapshotParser::default();
+        let mut buf: Vec<u8> = Vec::new();
+        parser.write_to(&mut buf, &data)?;
+        assert_eq!(String::from_utf8_lossy(&buf), "USER_ID,BALANCE,AS_OF\n10,300,1633036860000\n20,-5,1633036860000\n");
+        assert_eq!(parser.from_read(&mut buf.as_slice())?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_invalid() {
+        let parser = SnapshotParser::default();
+        assert!(matches!(parser.from_read(&mut "10,abc,0\n".as_bytes()), Err(ParserError::InvalidValue { tx_numb: 0 })));
+        assert!(matches!(parser.from_read(&mut "10,5\n".as_bytes()), Err(ParserError::InvalidCSVStructure(0))));
+    }
+
+    #[test]
+    fn test_reconcile() -> Result<(), Box<dyn std::error::Error>> {
+        let opening = vec![record(10, 100, 0), record(20, 50, 0), record(40, 7, 0)];
+        let data = vec![test_tx(1, TransactionType::TRANSFER, 10, 30, 40).timestamp(1).build()?];
+        let closing = vec![record(10, 60, 2), record(20, 55, 2), record(30, 40, 2)];
+
+        let report = reconcile(&opening, &data, &closing);
+        assert_eq!(report.mismatches, vec![
+            BalanceMismatch { user_id: 20, computed: 50, reported: 55 },
+            BalanceMismatch { user_id: 40, computed: 7, reported: 0 },
+        ]);
+        Ok(())
+    }
+}
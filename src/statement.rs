@@ -0,0 +1,242 @@
+use std::str::FromStr;
+
+use strum_macros::Display;
+
+use crate::{
+    Transaction, TransactionStatus,
+    amount::AmountFormat,
+    calendar::datetime_label,
+    error::ParserError,
+    ledger::{signed_amount, user_delta},
+};
+
+/// Форматы выписки
+#[derive(Display, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StatementFormat {
+    /// - TXT – текстовая таблица
+    TXT,
+    /// - CSV – формат с разделителем CSV
+    CSV,
+    /// - HTML – HTML-страница с таблицей
+    HTML,
+}
+
+impl FromStr for StatementFormat {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "txt" => Ok(Self::TXT),
+            "csv" => Ok(Self::CSV),
+            "html" => Ok(Self::HTML),
+            _ => Err(ParserError::InvalidFormat),
+        }
+    }
+}
+
+/// Строка выписки
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StatementLine {
+    /// - tx – транзакция
+    pub tx: Transaction,
+    /// - signed_amount – сумма с точки зрения пользователя: положительная - зачисление, отрицательная - списание
+    pub signed_amount: i128,
+    /// - running_balance – баланс после транзакции (учитываются только транзакции в статусе SUCCESS)
+    pub running_balance: i128,
+}
+
+/// Выписка по счету пользователя за период
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Statement {
+    /// - user_id – идентификатор пользователя
+    pub user_id: u64,
+    /// - from_ts – начало периода (включительно), Unix epoch в миллисекундах
+    pub from_ts: u64,
+    /// - to_ts – окончание периода (не включительно), Unix epoch в миллисекундах
+    pub to_ts: u64,
+    /// - opening_balance – баланс на начало периода
+    pub opening_balance: i128,
+    /// - lines – транзакции периода в порядке времени
+    pub lines: Vec<StatementLine>,
+    /// - closing_balance – баланс на конец периода
+    pub closing_balance: i128,
+}
+
+/// Функция формирования выписки по счету пользователя
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции
+/// * user_id: u64 - идентификатор пользователя (отправитель или получатель транзакции)
+/// * from_ts: u64 - начало периода (включительно)
+/// * to_ts: u64 - окончание периода (не включительно)
+///
+/// # Возвращаемое значение
+/// Statement. Начальный баланс рассчитывается по успешным транзакциям до начала периода.
+/// В выписку попадают транзакции периода в любом статусе, на баланс влияют только успешные.
+///
+/// # Пример
+/// ```ignore
+/// let statement = build_statement(&tx_vec, 42, 1633036800000, 1635724800000);
+/// write_statement(&mut out_file, &statement, StatementFormat::HTML, &AmountFormat::for_currency("RUB"))?;
+/// ```
+pub fn build_statement(data: &[Transaction], user_id: u64, from_ts: u64, to_ts: u64) -> Statement {
+    let mut involved: Vec<&Transaction> = data
+        .iter()
+        .filter(|tx| tx.from_user_id == user_id || tx.to_user_id == user_id)
+        .collect();
+    involved.sort_by_key(|tx| tx.timestamp);
+
+    let opening_balance: i128 = involved
+        .iter()
+        .filter(|tx| tx.timestamp < from_ts)
+        .map(|tx| user_delta(tx, user_id))
+        .sum();
+
+    let mut running_balance = opening_balance;
+    let lines: Vec<StatementLine> = involved
+        .into_iter()
+        .filter(|tx| tx.timestamp >= from_ts && tx.timestamp < to_ts)
+        .map(|tx| {
+            running_balance += user_delta(tx, user_id);
+            StatementLine { tx: tx.clone(), signed_amount: signed_amount(tx, user_id), running_balance }
+        })
+        .collect();
+
+    Statement { user_id, from_ts, to_ts, opening_balance, lines, closing_balance: running_balance }
+}
+
+/// Функция записи выписки
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер)
+/// * statement - выписка
+/// * format - формат выписки
+/// * amount_format - формат записи сумм
+pub fn write_statement<W: std::io::Write>(target: &mut W, statement: &Statement, format: StatementFormat,
+    amount_format: &AmountFormat) -> Result<(), ParserError> {
+
+    let amount = |val: i128| format_signed(amount_format, val);
+    let mut result_str = String::new();
+
+    match format {
+        StatementFormat::TXT => {
+            result_str += format!("Statement for user {}\n", statement.user_id).as_str();
+            result_str += format!("Period: {} - {}\n", datetime_label(statement.from_ts),
+                datetime_label(statement.to_ts)).as_str();
+            result_str += format!("Opening balance: {}\n\n", amount(statement.opening_balance)).as_str();
+            result_str += format!("{:<19}  {:>20}  {:<10}  {:<7}  {:>16}  {:>16}  {}\n",
+                "DATE", "TX_ID", "TX_TYPE", "STATUS", "AMOUNT", "BALANCE", "DESCRIPTION").as_str();
+            for line in &statement.lines {
+                result_str += format!("{:<19}  {:>20}  {:<10}  {:<7}  {:>16}  {:>16}  {}\n",
+                    datetime_label(line.tx.timestamp), line.tx.tx_id, line.tx.tx_type.to_string(),
+                    line.tx.status.to_string(), amount(line.signed_amount), amount(line.running_balance),
+                    line.tx.description).as_str();
+            }
+            result_str += format!("\nClosing balance: {}\n", amount(statement.closing_balance)).as_str();
+        }
+        StatementFormat::CSV => {
+            result_str += "TX_ID,TIMESTAMP,TX_TYPE,STATUS,AMOUNT,BALANCE,DESCRIPTION\n";
+            result_str += format!(",{},OPENING_BALANCE,,,\"{}\",\"\"\n", statement.from_ts,
+                amount(statement.opening_balance)).as_str();
+            for line in &statement.lines {
+                result_str += format!("{},{},{},{},\"{}\",\"{}\",\"{}\"\n", line.tx.tx_id, line.tx.timestamp,
+                    line.tx.tx_type, line.tx.status, amount(line.signed_amount), amount(line.running_balance),
+                    line.tx.description).as_str();
+            }
+            result_str += format!(",{},CLOSING_BALANCE,,,\"{}\",\"\"\n", statement.to_ts,
+                amount(statement.closing_balance)).as_str();
+        }
+        StatementFormat::HTML => {
+            result_str += "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Statement</title></head>\n<body>\n";
+            result_str += format!("<h1>Statement for user {}</h1>\n", statement.user_id).as_str();
+            result_str += format!("<p>Period: {} - {}</p>\n", datetime_label(statement.from_ts),
+                datetime_label(statement.to_ts)).as_str();
+            result_str += format!("<p>Opening balance: {}</p>\n", amount(statement.opening_balance)).as_str();
+            result_str += "<table>\n<tr><th>Date</th><th>Tx id</th><th>Type</th><th>Status</th>\
+                <th>Amount</th><th>Balance</th><th>Description</th></tr>\n";
+            for line in &statement.lines {
+                let class = if line.tx.status == TransactionStatus::SUCCESS { "" } else { " class=\"inactive\"" };
+                result_str += format!("<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    class, datetime_label(line.tx.timestamp), line.tx.tx_id, line.tx.tx_type, line.tx.status,
+                    amount(line.signed_amount), amount(line.running_balance), escape_html(&line.tx.description)).as_str();
+            }
+            result_str += "</table>\n";
+            result_str += format!("<p>Closing balance: {}</p>\n", amount(statement.closing_balance)).as_str();
+            result_str += "</body>\n</html>\n";
+        }
+    }
+
+    target.write_all(result_str.as_bytes())?;
+    Ok(())
+}
+
+fn format_signed(amount_format: &AmountFormat, val: i128) -> String {
+    let sign = if val < 0 { "-" } else { "" };
+    match u64::try_from(val.unsigned_abs()) {
+        Ok(abs) => sign.to_owned() + amount_format.format(abs).as_str(),
+        Err(_) => val.to_string(),
+    }
+}
+
+fn escape_html(val: &str) -> String {
+    val.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, error::BuildError, test_tx};
+
+    fn example_statement() -> Result<Statement, BuildError> {
+        let data = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 1000).timestamp(100).description("<tx 1>").build()?,
+            test_tx(4, TransactionType::WITHDRAWAL, 10, 0, 50).timestamp(400).status(TransactionStatus::FAILURE)
+                .description("<tx 4>").build()?,
+            test_tx(2, TransactionType::TRANSFER, 10, 20, 300).timestamp(200).description("<tx 2>").build()?,
+            test_tx(3, TransactionType::TRANSFER, 20, 10, 25).timestamp(300).description("<tx 3>").build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 7).timestamp(500).description("<tx 5>").build()?,
+            test_tx(6, TransactionType::DEPOSIT, 0, 30, 7).timestamp(300).description("<tx 6>").build()?,
+        ];
+        Ok(build_statement(&data, 10, 200, 500))
+    }
+
+    #[test]
+    fn test_build_statement() -> Result<(), BuildError> {
+        let statement = example_statement()?;
+        assert_eq!(statement.opening_balance, 1000);
+        assert_eq!(statement.closing_balance, 725);
+        let lines: Vec<(u64, i128, i128)> = statement
+            .lines
+            .iter()
+            .map(|line| (line.tx.tx_id, line.signed_amount, line.running_balance))
+            .collect();
+        assert_eq!(lines, vec![(2, -300, 700), (3, 25, 725), (4, -50, 725)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_statement() -> Result<(), Box<dyn std::error::Error>> {
+        let statement = example_statement()?;
+        let amount_format = AmountFormat::decimal(2);
+
+        let mut buf: Vec<u8> = Vec::new();
+        write_statement(&mut buf, &statement, StatementFormat::CSV, &amount_format)?;
+        let csv = String::from_utf8(buf)?;
+        assert!(csv.starts_with("TX_ID,TIMESTAMP,TX_TYPE,STATUS,AMOUNT,BALANCE,DESCRIPTION\n,200,OPENING_BALANCE,,,\"10.00\",\"\"\n"));
+        assert!(csv.contains("2,200,TRANSFER,SUCCESS,\"-3.00\",\"7.00\",\"<tx 2>\"\n"));
+        assert!(csv.ends_with(",500,CLOSING_BALANCE,,,\"7.25\",\"\"\n"));
+
+        let mut buf: Vec<u8> = Vec::new();
+        write_statement(&mut buf, &statement, StatementFormat::HTML, &amount_format)?;
+        let html = String::from_utf8(buf)?;
+        assert!(html.contains("<td>&lt;tx 3&gt;</td>"));
+        assert!(html.contains("<tr class=\"inactive\">"));
+
+        let mut buf: Vec<u8> = Vec::new();
+        write_statement(&mut buf, &statement, StatementFormat::TXT, &amount_format)?;
+        assert!(String::from_utf8(buf)?.contains("Closing balance: 7.25\n"));
+        Ok(())
+    }
+}
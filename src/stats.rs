@@ -0,0 +1,313 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use serde::Serialize;
+use strum_macros::Display;
+
+use crate::{
+    Transaction, TransactionStatus, TransactionType,
+    calendar::{MS_PER_HOUR, date_label, day_of},
+    error::ParserError,
+};
+
+/// Способы группировки статистики
+#[derive(Display, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GroupBy {
+    /// - all – без группировки
+    #[strum(serialize = "all")]
+    All,
+    /// - type – по типу транзакции
+    #[strum(serialize = "type")]
+    Type,
+    /// - status – по статусу транзакции
+    #[strum(serialize = "status")]
+    Status,
+    /// - user – по пользователю (см. [`Transaction::participants`]), перевод учитывается у обоих участников
+    #[strum(serialize = "user")]
+    User,
+    /// - day – по суткам (UTC)
+    #[strum(serialize = "day")]
+    Day,
+    /// - hour – по часам (UTC)
+    #[strum(serialize = "hour")]
+    Hour,
+}
+
+impl FromStr for GroupBy {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "type" => Ok(Self::Type),
+            "status" => Ok(Self::Status),
+            "user" => Ok(Self::User),
+            "day" => Ok(Self::Day),
+            "hour" => Ok(Self::Hour),
+            _ => Err(ParserError::InvalidGrouping(s.to_string())),
+        }
+    }
+}
+
+/// Статистика сумм группы транзакций
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct Summary {
+    /// - count – число транзакций
+    pub count: u64,
+    /// - sum – сумма
+    pub sum: u128,
+    /// - min – минимальная сумма
+    pub min: u64,
+    /// - max – максимальная сумма
+    pub max: u64,
+    /// - mean – средняя сумма
+    pub mean: f64,
+    /// - p50 – медиана (метод ближайшего ранга)
+    pub p50: u64,
+    /// - p90 – 90-й процентиль
+    pub p90: u64,
+    /// - p99 – 99-й процентиль
+    pub p99: u64,
+}
+
+impl Summary {
+    /// Функция расчета статистики по суммам
+    ///
+    /// # Возвращаемое значение
+    /// None для пустого набора сумм
+    pub fn of(amounts: &mut [u64]) -> Option<Self> {
+        if amounts.is_empty() {
+            return None;
+        }
+        amounts.sort_unstable();
+
+        let count = amounts.len() as u64;
+        let sum: u128 = amounts.iter().map(|amount| *amount as u128).sum();
+        let percentile = |p: u64| amounts[((p * count).div_ceil(100).max(1) - 1) as usize];
+
+        Some(Self {
+            count,
+            sum,
+            min: amounts[0],
+            max: amounts[amounts.len() - 1],
+            mean: sum as f64 / count as f64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+        })
+    }
+}
+
+/// Статистика группы транзакций
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct GroupStats {
+    /// - key – значение ключа группы
+    pub key: String,
+    /// - summary – статистика сумм
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// Результат расчета статистики
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct StatsReport {
+    /// - group_by – способ группировки
+    pub group_by: String,
+    /// - groups – статистика групп в порядке ключей
+    pub groups: Vec<GroupStats>,
+}
+
+/// Направление движения по счету пользователя
+#[derive(Display, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub(crate) enum Side {
+    #[strum(serialize = "DEBIT")]
+    Debit,
+    #[strum(serialize = "CREDIT")]
+    Credit,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub(crate) enum Bucket {
+    All,
+    Type(TransactionType),
+    Status(TransactionStatus),
+    User(u64),
+    UserSide(u64, Side),
+    Day(u64),
+    Hour(u64),
+}
+
+impl Bucket {
+    /// Группы транзакции (перевод относится к группам обоих участников)
+    pub(crate) fn of(tx: &Transaction, group_by: GroupBy) -> Vec<Bucket> {
+        match group_by {
+            GroupBy::All => vec![Bucket::All],
+            GroupBy::Type => vec![Bucket::Type(tx.tx_type.clone())],
+            GroupBy::Status => vec![Bucket::Status(tx.status.clone())],
+            GroupBy::User => {
+                let mut users = tx.participants();
+                users.dedup();
+                users.into_iter().map(Bucket::User).collect()
+            }
+            GroupBy::Day => vec![Bucket::Day(day_of(tx.timestamp))],
+            GroupBy::Hour => vec![Bucket::Hour(tx.timestamp / MS_PER_HOUR)],
+        }
+    }
+
+    /// Группы транзакции с учетом направления: перевод относится к группе списания отправителя
+    /// и к группе зачисления получателя
+    pub(crate) fn directed(tx: &Transaction, group_by: GroupBy) -> Vec<Bucket> {
+        match (group_by, &tx.tx_type) {
+            (GroupBy::User, TransactionType::DEPOSIT) => vec![Bucket::UserSide(tx.to_user_id, Side::Credit)],
+            (GroupBy::User, TransactionType::WITHDRAWAL) => vec![Bucket::UserSide(tx.from_user_id, Side::Debit)],
+            (GroupBy::User, TransactionType::TRANSFER | TransactionType::EMPTY) => vec![
+                Bucket::UserSide(tx.from_user_id, Side::Debit),
+                Bucket::UserSide(tx.to_user_id, Side::Credit),
+            ],
+            _ => Self::of(tx, group_by),
+        }
+    }
+
+    pub(crate) fn label(&self) -> String {
+        match self {
+            Self::All => "ALL".to_owned(),
+            Self::Type(tx_type) => tx_type.to_string(),
+            Self::Status(status) => status.to_string(),
+            Self::User(user_id) => user_id.to_string(),
+            Self::UserSide(user_id, side) => format!("{} {}", user_id, side),
+            Self::Day(day) => date_label(*day),
+            Self::Hour(hour) => {
+                format!("{} {:02}:00", date_label(day_of(hour * MS_PER_HOUR)), hour % 24)
+            }
+        }
+    }
+}
+
+/// Функция расчета статистики сумм транзакций
+///
+/// # Аргументы
+///
+/// * data: &[Transaction] - транзакции
+/// * group_by: GroupBy - способ группировки
+///
+/// # Возвращаемое значение
+/// StatsReport, группы упорядочены по ключу (тип, статус, пользователь, время)
+///
+/// # Пример
+/// ```ignore
+/// let report = compute_stats(&tx_vec, GroupBy::Type);
+/// write_stats(&mut std::io::stdout(), &report, StatsOutput::Table)?;
+/// ```
+pub fn compute_stats(data: &[Transaction], group_by: GroupBy) -> StatsReport {
+    let mut buckets: BTreeMap<Bucket, Vec<u64>> = BTreeMap::new();
+
+    for tx in data {
+        for key in Bucket::of(tx, group_by) {
+            buckets.entry(key).or_default().push(tx.amount);
+        }
+    }
+
+    let groups = buckets
+        .into_iter()
+        .filter_map(|(key, mut amounts)| {
+            Summary::of(&mut amounts).map(|summary| GroupStats { key: key.label(), summary })
+        })
+        .collect();
+
+    StatsReport { group_by: group_by.to_string(), groups }
+}
+
+/// Форматы вывода статистики
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StatsOutput {
+    /// - Table – текстовая таблица
+    Table,
+    /// - Json – документ JSON
+    Json,
+}
+
+impl FromStr for StatsOutput {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            _ => Err(ParserError::InvalidFormat),
+        }
+    }
+}
+
+/// Функция записи статистики
+///
+/// # Аргументы
+///
+/// * target - получатель данных реализуйщий трейт Write (файл, буфер, stdout)
+/// * report - статистика
+/// * output - формат вывода
+pub fn write_stats<W: std::io::Write>(target: &mut W, report: &StatsReport, output: StatsOutput) -> Result<(), ParserError> {
+    match output {
+        StatsOutput::Json => {
+            serde_json::to_writer_pretty(&mut *target, report)?;
+            target.write_all(b"\n")?;
+        }
+        StatsOutput::Table => {
+            let mut result_str = format!("{:<20} {:>10} {:>24} {:>20} {:>20} {:>22} {:>20} {:>20} {:>20}\n",
+                report.group_by.to_uppercase(), "COUNT", "SUM", "MIN", "MAX", "MEAN", "P50", "P90", "P99");
+            for group in &report.groups {
+                let summary = &group.summary;
+                result_str += format!("{:<20} {:>10} {:>24} {:>20} {:>20} {:>22.2} {:>20} {:>20} {:>20}\n",
+                    group.key, summary.count, summary.sum, summary.min, summary.max, summary.mean,
+                    summary.p50, summary.p90, summary.p99).as_str();
+            }
+            target.write_all(result_str.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tx;
+    use crate::calendar::MS_PER_DAY;
+
+    #[test]
+    fn test_summary() {
+        let mut amounts: Vec<u64> = (1..=100).rev().collect();
+        let summary = Summary::of(&mut amounts);
+        assert_eq!(summary, Some(Summary { count: 100, sum: 5050, min: 1, max: 100, mean: 50.5, p50: 50, p90: 90,
+            p99: 99 }));
+        assert_eq!(Summary::of(&mut [7]).map(|summary| summary.p99), Some(7));
+        assert_eq!(Summary::of(&mut []), None);
+    }
+
+    #[test]
+    fn test_compute_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let data = vec![
+            test_tx(100, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(40, TransactionType::TRANSFER, 10, 20, 40).timestamp(MS_PER_HOUR).build()?,
+            test_tx(10, TransactionType::WITHDRAWAL, 20, 0, 10).timestamp(MS_PER_DAY + 5).build()?,
+        ];
+
+        let by_user = compute_stats(&data, "user".parse()?);
+        let users: Vec<(&str, u64, u128)> = by_user
+            .groups
+            .iter()
+            .map(|group| (group.key.as_str(), group.summary.count, group.summary.sum))
+            .collect();
+        assert_eq!(users, vec![("10", 2, 140), ("20", 2, 50)]);
+
+        let by_hour = compute_stats(&data, GroupBy::Hour);
+        let hours: Vec<&str> = by_hour.groups.iter().map(|group| group.key.as_str()).collect();
+        assert_eq!(hours, vec!["1970-01-01 00:00", "1970-01-01 01:00", "1970-01-02 00:00"]);
+
+        let by_type = compute_stats(&data, GroupBy::Type);
+        let mut buf: Vec<u8> = Vec::new();
+        write_stats(&mut buf, &by_type, StatsOutput::Json)?;
+        let json: serde_json::Value = serde_json::from_slice(&buf)?;
+        assert_eq!(json["group_by"], "type");
+        assert_eq!(json["groups"][0]["key"], "DEPOSIT");
+        assert_eq!(json["groups"][0]["sum"], 100);
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use strum_macros::Display;
+
+use crate::Transaction;
+
+/// Результат трехстороннего сравнения записей с одним tx_id
+#[derive(Display, Serialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MergeStatus {
+    /// Записи не изменились
+    #[strum(serialize = "UNCHANGED")]
+    Unchanged,
+    /// Записи изменены только в первом наборе
+    #[strum(serialize = "LEFT_CHANGED")]
+    LeftChanged,
+    /// Записи изменены только во втором наборе
+    #[strum(serialize = "RIGHT_CHANGED")]
+    RightChanged,
+    /// Записи изменены в обоих наборах одинаково
+    #[strum(serialize = "BOTH_SAME")]
+    BothSame,
+    /// Записи изменены в наборах по-разному
+    #[strum(serialize = "CONFLICT")]
+    Conflict,
+}
+
+/// Конфликт изменений записей с одним tx_id, пустой список - записи отсутствуют в наборе
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct MergeConflict {
+    /// - tx_id – идентификатор транзакции
+    pub tx_id: u64,
+    /// - base – записи базового набора
+    pub base: Vec<Transaction>,
+    /// - left – записи первого набора
+    pub left: Vec<Transaction>,
+    /// - right – записи второго набора
+    pub right: Vec<Transaction>,
+}
+
+/// Результат трехстороннего сравнения наборов
+#[derive(Serialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct MergeReport {
+    /// - statuses – результат сравнения для каждого tx_id
+    pub statuses: BTreeMap<u64, MergeStatus>,
+    /// - merged – объединенный набор без конфликтующих записей, упорядочен по tx_id
+    pub merged: Vec<Transaction>,
+    /// - conflicts – конфликты, упорядочены по tx_id
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeReport {
+    /// Возвращает число tx_id с результатом сравнения status
+    pub fn count(&self, status: MergeStatus) -> usize {
+        self.statuses.values().filter(|val| **val == status).count()
+    }
+}
+
+/// Функция трехстороннего сравнения наборов транзакций, измененных относительно общего базового набора
+///
+/// Для каждого tx_id сравниваются записи базового, первого и второго наборов (повторы записей учитываются,
+/// добавление и удаление записи считаются изменением). Объединенный набор содержит неизмененные записи
+/// базового набора и изменения обоих наборов; записи с конфликтующими изменениями в него не включаются
+/// и возвращаются отдельно.
+///
+/// # Аргументы
+///
+/// * base: &[Transaction] - базовый набор транзакций
+/// * left_side: &[Transaction] - первый набор транзакций
+/// * right_side: &[Transaction] - второй набор транзакций
+///
+/// # Возвращаемое значение
+/// MergeReport
+///
+/// # Пример
+/// ```ignore
+/// let report = merge3(&snapshot, &bank_vec, &processing_vec);
+/// println!("{} conflicts", report.count(MergeStatus::Conflict));
+/// ```
+pub fn merge3(base: &[Transaction], left_side: &[Transaction], right_side: &[Transaction]) -> MergeReport {
+    let mut groups: BTreeMap<u64, [Vec<Transaction>; 3]> = BTreeMap::new();
+    for (side, data) in [base, left_side, right_side].into_iter().enumerate() {
+        for tx in data {
+            groups.entry(tx.tx_id).or_default()[side].push(tx.clone());
+        }
+    }
+
+    let mut report = MergeReport::default();
+    for (tx_id, [mut base, mut left, mut right]) in groups {
+        base.sort();
+        left.sort();
+        right.sort();
+
+        let status = match (left != base, right != base) {
+            (false, false) => MergeStatus::Unchanged,
+            (true, false) => MergeStatus::LeftChanged,
+            (false, true) => MergeStatus::RightChanged,
+            (true, true) if left == right => MergeStatus::BothSame,
+            (true, true) => MergeStatus::Conflict,
+        };
+        report.statuses.insert(tx_id, status);
+
+        match status {
+            MergeStatus::Unchanged | MergeStatus::LeftChanged | MergeStatus::BothSame => report.merged.extend(left),
+            MergeStatus::RightChanged => report.merged.extend(right),
+            MergeStatus::Conflict => report.conflicts.push(MergeConflict { tx_id, base, left, right }),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TransactionType, test_tx};
+
+    #[test]
+    fn test_merge3() -> Result<(), Box<dyn std::error::Error>> {
+        let base = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(6, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+        ];
+        let left = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 150).build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 200).build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 300).build()?,
+            test_tx(7, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+        ];
+        let right = vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 120).build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 200).build()?,
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 400).build()?,
+            test_tx(8, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+        ];
+
+        let report = merge3(&base, &left, &right);
+
+        let statuses: Vec<(u64, MergeStatus)> = report.statuses.iter().map(|(id, status)| (*id, *status)).collect();
+        assert_eq!(statuses, vec![
+            (1, MergeStatus::Unchanged),
+            (2, MergeStatus::LeftChanged),
+            (3, MergeStatus::RightChanged),
+            (4, MergeStatus::BothSame),
+            (5, MergeStatus::Conflict),
+            (6, MergeStatus::BothSame),
+            (7, MergeStatus::LeftChanged),
+            (8, MergeStatus::RightChanged),
+        ]);
+        assert_eq!(report.merged, vec![
+            test_tx(1, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(2, TransactionType::DEPOSIT, 0, 10, 150).build()?,
+            test_tx(3, TransactionType::DEPOSIT, 0, 10, 120).build()?,
+            test_tx(4, TransactionType::DEPOSIT, 0, 10, 200).build()?,
+            test_tx(7, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+            test_tx(8, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+        ]);
+        assert_eq!(report.conflicts, vec![MergeConflict { tx_id: 5, base: vec![
+            test_tx(5, TransactionType::DEPOSIT, 0, 10, 100).build()?,
+        ], left: vec![test_tx(5, TransactionType::DEPOSIT, 0, 10, 300).build()?],
+            right: vec![test_tx(5, TransactionType::DEPOSIT, 0, 10, 400).build()?] }]);
+        assert_eq!(report.count(MergeStatus::BothSame), 2);
+        Ok(())
+    }
+}
@@ -1,11 +1,65 @@
 use crate::{
-    ParserError, Transaction, TransactionsFormatType, TransactionsParser, txt_to_json_str};
+    amount::AmountFormat,
+    ParserError, Transaction, TransactionBuilder, TransactionsFormatType, TransactionsParser,
+    parse_amount_value, parse_status_value, parse_type_value, parse_u64_value};
 
 #[derive(Default)]
 
 /// Парсер текстового формата TXT
 pub struct TxtParser {
-    
+    allow_empty: bool,
+    amount_format: AmountFormat,
+}
+
+impl TxtParser {
+    /// Конструктор парсера в режиме обратной совместимости:
+    /// значения `EMPTY` типа и статуса транзакции допускаются при чтении
+    pub fn with_empty_compat() -> Self {
+        Self { allow_empty: true, ..Default::default() }
+    }
+
+    /// Устанавливает формат записи сумм (по умолчанию - целые минорные единицы)
+    pub fn amount_format(mut self, amount_format: AmountFormat) -> Self {
+        self.amount_format = amount_format;
+        self
+    }
+
+    /// Функция чтения транзакции из блока строк формата TXT
+    /// * block - строки одной записи (записи разделяются пустой строкой)
+    /// * numb - номер транзакции в наборе
+    pub(crate) fn parse_record(&self, block: &str, numb: usize) -> Result<Transaction, ParserError> {
+        let tmp_vec: Vec<&str> = block
+            .split("\n")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .filter(|s| !s.starts_with("# Record"))
+            .collect();
+
+        let mut builder = TransactionBuilder::new().allow_empty(self.allow_empty);
+
+        for s in tmp_vec {
+            let (key, value) = s
+                .split_once(":")
+                .ok_or_else(|| ParserError::InvalidTxtStrStructure(s.to_string()))?;
+            let value = value.trim();
+
+            builder = match key.trim() {
+                "TX_ID" => builder.tx_id(parse_u64_value(value, numb)?),
+                "TX_TYPE" => builder.tx_type(parse_type_value(value, numb)?),
+                "FROM_USER_ID" => builder.from_user_id(parse_u64_value(value, numb)?),
+                "TO_USER_ID" => builder.to_user_id(parse_u64_value(value, numb)?),
+                "AMOUNT" => builder.amount(parse_amount_value(value, numb, &self.amount_format)?),
+                "TIMESTAMP" => builder.timestamp(parse_u64_value(value, numb)?),
+                "STATUS" => builder.status(parse_status_value(value, numb)?),
+                "DESCRIPTION" => builder.description(serde_json::from_str::<String>(value)?),
+                _ => return Err(ParserError::InvalidTxtStrStructure(s.to_string())),
+            };
+        }
+
+        builder
+            .build()
+            .map_err(|source| ParserError::InvalidTransaction { tx_numb: numb, source })
+    }
 }
 
 impl TransactionsParser for TxtParser {
@@ -27,27 +81,13 @@ impl TransactionsParser for TxtParser {
             .collect();
 
 
-        for stx in str_arr {
-
-            let tmp_vec: Vec<&str> = stx
-            .split("\n")
-            .map(|s| s.trim())
-            .filter(|s| !s.contains("# Record"))
-            .collect();
-
-            let json_vec: Vec<_> = tmp_vec  
-            .into_iter()  
-            .filter_map(|s| txt_to_json_str(s).ok())
-            .collect();
-
-            let str_tx = "{".to_owned() + &json_vec.join(",") + "}";
-            let tx: Transaction = serde_json::from_str(&str_tx)?;
-            result.push(tx);
+        for (numb, stx) in str_arr.iter().enumerate() {
+            result.push(self.parse_record(stx, numb)?);
         }
         Ok(result)
     }
 
-    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &Vec<Transaction>) -> Result<(), ParserError> {
+    fn write_to<W: std::io::Write>(&self, target: &mut W, data: &[Transaction]) -> Result<(), ParserError> {
 
         let mut result_str = String::new();
 
@@ -59,7 +99,7 @@ impl TransactionsParser for TxtParser {
                 "TX_TYPE: " + tx.tx_type.to_string().as_str() + "\n" +
                 "TO_USER_ID: " + tx.to_user_id.to_string().as_str() + "\n" +
                 "FROM_USER_ID: " + tx.from_user_id.to_string().as_str() + "\n" +
-                "AMOUNT: " + tx.amount.to_string().as_str() + "\n" +
+                "AMOUNT: " + self.amount_format.format(tx.amount).as_str() + "\n" +
                 "TIMESTAMP: " + tx.timestamp.to_string().as_str() + "\n" +
                 "STATUS: " + tx.status.to_string().as_str() + "\n" +  
                 "DESCRIPTION: " + "\"" + tx.description.to_string().as_str() + "\"" + "\n" +
@@ -69,4 +109,4 @@ impl TransactionsParser for TxtParser {
         target.write_all(result_str.as_bytes())?;
         Ok(())
     }
-}
\ No newline at end of file
+}